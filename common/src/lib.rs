@@ -0,0 +1,451 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! Helpers shared by the day binaries.
+
+use flate2::read::GzDecoder;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+use std::str::FromStr;
+
+/// The first two bytes of every gzip file
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Errors shared by the day binaries
+#[derive(Debug, PartialEq)]
+pub enum AocError {
+    /// The token at the given index (starting at 0) in a list isn't valid
+    Parse { token: String, index: usize },
+    /// A result doesn't fit in the integer type
+    Overflow,
+    /// Reading the input failed
+    Io(String),
+    /// The input was read but can't be solved, like a program that fails
+    Data(String),
+    /// The command line is missing an argument or has one that isn't valid
+    Usage(String),
+    /// Something that shouldn't happen did; this is a bug
+    Internal(String),
+}
+
+impl fmt::Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AocError::Parse { token, index } => {
+                write!(f, "can't parse {:?} (item {})", token, index)
+            }
+            AocError::Overflow => write!(f, "arithmetic overflow"),
+            AocError::Io(msg) | AocError::Data(msg) | AocError::Usage(msg) => {
+                write!(f, "{}", msg)
+            }
+            AocError::Internal(msg) => write!(f, "internal error: {}", msg),
+        }
+    }
+}
+
+impl Error for AocError {}
+
+impl From<io::Error> for AocError {
+    fn from(err: io::Error) -> AocError {
+        AocError::Io(err.to_string())
+    }
+}
+
+/// Return the process exit code for the error
+pub fn exit_code_for(err: &AocError) -> i32 {
+    match err {
+        AocError::Parse { .. } | AocError::Overflow | AocError::Io(_) | AocError::Data(_) => {
+            exitcode::DATAERR
+        }
+        AocError::Usage(_) => exitcode::USAGE,
+        AocError::Internal(_) => exitcode::SOFTWARE,
+    }
+}
+
+/// Split the input on the delimiter and parse every item; surrounding
+/// whitespace is ignored and empty input is an empty list
+pub fn parse_list<T: FromStr>(input: &str, delim: char) -> Result<Vec<T>, AocError> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(vec![]);
+    }
+    input
+        .split(delim)
+        .enumerate()
+        .map(|(index, token)| {
+            let token = token.trim();
+            token.parse::<T>().map_err(|_| AocError::Parse {
+                token: token.to_string(),
+                index,
+            })
+        })
+        .collect()
+}
+
+/// A day's puzzle solution
+///
+/// The answers are text to show, not values to check: when the input can't
+/// be solved they're a message instead, like the parse error for input that
+/// doesn't parse or "none" when there's no answer.
+pub trait Solution {
+    /// Return the answer to part one for the puzzle input
+    fn part1(&self, input: &str) -> String;
+
+    /// Return the answer to part two for the puzzle input
+    fn part2(&self, input: &str) -> String;
+}
+
+/// Read everything from the given reader into a string
+pub fn read_from<R: Read>(mut reader: R) -> io::Result<String> {
+    let mut contents = String::new();
+    reader.read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+/// Open the data file at the given path, decompressing it if it's gzipped
+fn open_file(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let is_gzip = path.extension().is_some_and(|ext| ext == "gz")
+        || reader.fill_buf()?.starts_with(&GZIP_MAGIC);
+    if is_gzip {
+        Ok(Box::new(BufReader::new(GzDecoder::new(reader))))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// Open the data file at the given path, or stdin if there's no path, for
+/// reading a line at a time
+///
+/// Gzipped data files are decompressed transparently.
+pub fn open_input(path: Option<&str>) -> io::Result<Box<dyn BufRead>> {
+    match path {
+        Some(path) => open_file(Path::new(path)),
+        None => Ok(Box::new(BufReader::new(io::stdin()))),
+    }
+}
+
+/// Read the data file at the given path, or stdin if there's no path
+///
+/// Gzipped data files are decompressed transparently.
+pub fn read_input(path: Option<&str>) -> io::Result<String> {
+    read_from(open_input(path)?)
+}
+
+/// Format the answers for both parts as a JSON object
+pub fn format_json(part1: i64, part2: i64) -> String {
+    format_json_parts(Some(part1), Some(part2))
+}
+
+/// Format the answers that were computed as a JSON object, leaving out the
+/// parts that weren't
+pub fn format_json_parts(part1: Option<i64>, part2: Option<i64>) -> String {
+    let fields: Vec<String> = [("part1", part1), ("part2", part2)]
+        .iter()
+        .filter_map(|(key, val)| val.map(|val| format!("\"{}\": {}", key, val)))
+        .collect();
+    format!("{{{}}}", fields.join(", "))
+}
+
+/// Which parts of the puzzle to compute and print
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Part {
+    One,
+    Two,
+    Both,
+}
+
+impl Part {
+    /// Return whether part one should be computed
+    pub fn part1(self) -> bool {
+        self != Part::Two
+    }
+
+    /// Return whether part two should be computed
+    pub fn part2(self) -> bool {
+        self != Part::One
+    }
+}
+
+/// Flags that take their value in the following argument
+const VALUE_FLAGS: [&str; 1] = ["--part"];
+
+/// Return the part picked with `--part 1`, `--part=1` or the same for 2, or
+/// both parts when the flag isn't given; the last one given wins
+pub fn selected_part(args: &[String]) -> Result<Part, AocError> {
+    let mut value = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--part" {
+            value = Some(args.next().map(|arg| arg.as_str()));
+        } else if let Some(part) = arg.strip_prefix("--part=") {
+            value = Some(Some(part));
+        }
+    }
+    match value {
+        None => Ok(Part::Both),
+        Some(Some("1")) => Ok(Part::One),
+        Some(Some("2")) => Ok(Part::Two),
+        Some(_) => Err(AocError::Usage("--part must be 1 or 2".to_string())),
+    }
+}
+
+/// Return whether the flag is in the args
+pub fn has_flag(args: &[String], flag: &str) -> bool {
+    args.iter().any(|arg| arg == flag)
+}
+
+/// Return the values given with `--flag=value` for the flag, in order
+pub fn flag_values<'a>(args: &'a [String], flag: &str) -> Vec<&'a str> {
+    let prefix = format!("{}=", flag);
+    args.iter()
+        .filter_map(|arg| arg.strip_prefix(&prefix))
+        .collect()
+}
+
+/// Return whether the `--json` flag is in the args
+pub fn wants_json(args: &[String]) -> bool {
+    has_flag(args, "--json")
+}
+
+/// Return the first argument that isn't a flag or a flag's value
+pub fn first_positional(args: &[String]) -> Option<&str> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if VALUE_FLAGS.contains(&arg.as_str()) {
+            args.next();
+        } else if !arg.starts_with("--") {
+            return Some(arg);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_parse_list() {
+        assert_eq!(parse_list::<i64>("1,-2, 3\n", ','), Ok(vec![1, -2, 3]));
+        assert_eq!(parse_list::<usize>("4 5 6", ' '), Ok(vec![4, 5, 6]));
+        assert_eq!(parse_list::<usize>("  \n", ','), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_parse_list_error() {
+        let err = parse_list::<usize>("1,-2,3", ',').unwrap_err();
+        assert_eq!(
+            err,
+            AocError::Parse {
+                token: "-2".to_string(),
+                index: 1
+            }
+        );
+        assert_eq!(err.to_string(), "can't parse \"-2\" (item 1)");
+
+        assert!(parse_list::<i64>("1,,3", ',').is_err());
+    }
+
+    #[test]
+    fn test_error_display() {
+        assert_eq!(AocError::Overflow.to_string(), "arithmetic overflow");
+        assert_eq!(
+            AocError::Usage("no datafile".to_string()).to_string(),
+            "no datafile"
+        );
+        assert_eq!(
+            AocError::Data("no answer".to_string()).to_string(),
+            "no answer"
+        );
+        assert_eq!(
+            AocError::Internal("oops".to_string()).to_string(),
+            "internal error: oops"
+        );
+
+        let err = AocError::from(io::Error::new(io::ErrorKind::NotFound, "no such file"));
+        assert_eq!(err, AocError::Io("no such file".to_string()));
+    }
+
+    #[test]
+    fn test_exit_code_for() {
+        let parse = AocError::Parse {
+            token: "x".to_string(),
+            index: 0,
+        };
+        assert_eq!(exit_code_for(&parse), exitcode::DATAERR);
+        assert_eq!(exit_code_for(&AocError::Overflow), exitcode::DATAERR);
+        assert_eq!(
+            exit_code_for(&AocError::Io("no such file".to_string())),
+            exitcode::DATAERR
+        );
+        assert_eq!(
+            exit_code_for(&AocError::Data("no answer".to_string())),
+            exitcode::DATAERR
+        );
+        assert_eq!(
+            exit_code_for(&AocError::Usage("no datafile".to_string())),
+            exitcode::USAGE
+        );
+        assert_eq!(
+            exit_code_for(&AocError::Internal("oops".to_string())),
+            exitcode::SOFTWARE
+        );
+    }
+
+    #[test]
+    fn test_read_from() {
+        let contents = read_from(Cursor::new("1,2,3\n")).unwrap();
+        assert_eq!(contents, "1,2,3\n");
+    }
+
+    #[test]
+    fn test_read_input_file() {
+        let path = std::env::temp_dir().join("common_test_read_input_file.txt");
+        std::fs::write(&path, "12\n14\n").unwrap();
+        let contents = read_input(path.to_str()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(contents, "12\n14\n");
+    }
+
+    #[test]
+    fn test_read_input_gzip() {
+        let plain = read_input(Some("fixtures/masses.txt")).unwrap();
+        let gzipped = read_input(Some("fixtures/masses.txt.gz")).unwrap();
+        assert_eq!(plain, "12\n14\n1969\n100756\n");
+        assert_eq!(gzipped, plain);
+    }
+
+    #[test]
+    fn test_read_input_gzip_magic() {
+        // Gzipped data is detected even without the .gz extension
+        let path = std::env::temp_dir().join("common_test_read_input_gzip_magic.txt");
+        std::fs::copy("fixtures/masses.txt.gz", &path).unwrap();
+        let contents = read_input(path.to_str()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(contents, "12\n14\n1969\n100756\n");
+    }
+
+    #[test]
+    fn test_open_input() {
+        let lines: Vec<String> = open_input(Some("fixtures/masses.txt.gz"))
+            .unwrap()
+            .lines()
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(lines, vec!["12", "14", "1969", "100756"]);
+        assert!(open_input(Some("/does/not/exist.txt")).is_err());
+    }
+
+    #[test]
+    fn test_read_input_missing_file() {
+        assert!(read_input(Some("/does/not/exist.txt")).is_err());
+    }
+
+    #[test]
+    fn test_format_json() {
+        assert_eq!(
+            format_json(3198291, 4795830),
+            "{\"part1\": 3198291, \"part2\": 4795830}"
+        );
+        assert_eq!(format_json(-1, 0), "{\"part1\": -1, \"part2\": 0}");
+    }
+
+    #[test]
+    fn test_format_json_parts() {
+        assert_eq!(format_json_parts(Some(5), None), "{\"part1\": 5}");
+        assert_eq!(format_json_parts(None, Some(7)), "{\"part2\": 7}");
+        assert_eq!(format_json_parts(None, None), "{}");
+    }
+
+    #[test]
+    fn test_has_flag() {
+        let args = vec!["datafile.txt".to_string(), "--verbose".to_string()];
+        assert!(has_flag(&args, "--verbose"));
+        assert!(!has_flag(&args, "--json"));
+    }
+
+    #[test]
+    fn test_flag_values() {
+        let args = vec![
+            "--input=1".to_string(),
+            "datafile.txt".to_string(),
+            "--input=-5".to_string(),
+            "--inputs=3".to_string(),
+        ];
+        assert_eq!(flag_values(&args, "--input"), vec!["1", "-5"]);
+        assert!(flag_values(&args, "--json").is_empty());
+        assert_eq!(first_positional(&args), Some("datafile.txt"));
+    }
+
+    #[test]
+    fn test_wants_json() {
+        let args = vec!["datafile.txt".to_string(), "--json".to_string()];
+        assert!(wants_json(&args));
+        assert!(!wants_json(&args[..1]));
+    }
+
+    #[test]
+    fn test_first_positional() {
+        let args = vec!["--json".to_string(), "datafile.txt".to_string()];
+        assert_eq!(first_positional(&args), Some("datafile.txt"));
+        assert_eq!(first_positional(&args[..1]), None);
+
+        // The value after --part isn't the data file
+        let args = vec![
+            "--part".to_string(),
+            "2".to_string(),
+            "datafile.txt".to_string(),
+        ];
+        assert_eq!(first_positional(&args), Some("datafile.txt"));
+        assert_eq!(first_positional(&args[..2]), None);
+    }
+
+    #[test]
+    fn test_selected_part() {
+        let args: Vec<String> = ["datafile.txt", "--part", "1"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        assert_eq!(selected_part(&args), Ok(Part::One));
+        assert!(Part::One.part1());
+        assert!(!Part::One.part2());
+
+        let args = vec!["--part".to_string(), "2".to_string()];
+        assert_eq!(selected_part(&args), Ok(Part::Two));
+        assert!(!Part::Two.part1());
+        assert!(Part::Two.part2());
+
+        assert_eq!(selected_part(&args[..0]), Ok(Part::Both));
+        assert!(Part::Both.part1() && Part::Both.part2());
+
+        let args = vec!["--part".to_string(), "3".to_string()];
+        assert_eq!(
+            selected_part(&args),
+            Err(AocError::Usage("--part must be 1 or 2".to_string()))
+        );
+        assert!(selected_part(&args[..1]).is_err());
+
+        let args = vec!["--part=2".to_string()];
+        assert_eq!(selected_part(&args), Ok(Part::Two));
+        let args = vec![
+            "--part=1".to_string(),
+            "--part".to_string(),
+            "2".to_string(),
+        ];
+        assert_eq!(selected_part(&args), Ok(Part::Two));
+        let args = vec![
+            "--part".to_string(),
+            "2".to_string(),
+            "--part=1".to_string(),
+        ];
+        assert_eq!(selected_part(&args), Ok(Part::One));
+        assert!(selected_part(&["--part=3".to_string()]).is_err());
+        assert!(selected_part(&["--part=".to_string()]).is_err());
+    }
+}
@@ -2,24 +2,489 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-use std::fs::File;
-use std::io::{self, BufRead};
+use std::io::{self, BufRead, Write};
 use std::path::Path;
+use std::str::FromStr;
+
+use rayon::prelude::*;
+
+/// Return the amount of fuel required for this mass alone.
+fn get_simple_fuel(mass: i32) -> i32 {
+    get_wide_simple_fuel(i64::from(mass)) as i32
+}
+
+/// Return the amount of fuel required for this mass alone, for masses too big
+/// for get_simple_fuel
+fn get_wide_simple_fuel(mass: i64) -> i64 {
+    ((mass / 3) - 2).max(0)
+}
 
 /// Return the amount of fuel required for this mass plus the fuel required.
 fn get_fuel(mass: i32) -> i32 {
+    get_fuel_with(i64::from(mass), 3, 2) as i32
+}
+
+/// Return the amount of fuel required for this mass plus the fuel required,
+/// for masses too big for get_fuel
+fn get_wide_fuel(mass: i64) -> i64 {
+    get_fuel_with(mass, 3, 2)
+}
+
+/// Return the fuel for this mass plus the fuel for the fuel, where each
+/// amount of fuel is `mass / divisor - offset`
+///
+/// Panics if the divisor is less than 2 or the offset is negative, since the
+/// fuel would never run out.
+fn get_fuel_with(mass: i64, divisor: i64, offset: i64) -> i64 {
+    assert!(
+        divisor >= 2 && offset >= 0,
+        "fuel never runs out with divisor {} and offset {}",
+        divisor,
+        offset
+    );
     let mut total = 0;
-    let mut fuel = (mass / 3) - 2;
+    let mut fuel = (mass / divisor) - offset;
     while fuel > 0 {
         total += fuel;
-        fuel = (fuel / 3) - 2;
+        fuel = (fuel / divisor) - offset;
     }
     total
 }
 
+/// Return the total fuel for the masses alone, without the fuel for the fuel
+#[cfg_attr(not(test), allow(dead_code))]
+fn sum_fuel_simple(masses: &[i64]) -> i64 {
+    masses.iter().map(|&mass| get_wide_simple_fuel(mass)).sum()
+}
+
+/// Return the total fuel for the masses and their fuel, adding up the modules
+/// across threads
+#[cfg_attr(not(test), allow(dead_code))]
+fn sum_fuel_parallel(masses: &[i64]) -> i64 {
+    masses.par_iter().map(|&mass| get_wide_fuel(mass)).sum()
+}
+
+/// Return the part 1 simple fuel total and the part 2 total with the fuel for
+/// the fuel, going over the masses once
+#[cfg_attr(not(test), allow(dead_code))]
+fn solve(masses: &[i64]) -> (i64, i64) {
+    masses.iter().fold((0, 0), |(simple_total, total), &mass| {
+        (
+            simple_total + get_wide_simple_fuel(mass),
+            total + get_wide_fuel(mass),
+        )
+    })
+}
+
+/// How to add up fuel when the total doesn't fit in an i64
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum OverflowPolicy {
+    /// Wrap around to the other end of the range
+    Wrapping,
+    /// Stop at i64::MAX
+    Saturating,
+    /// Return an error
+    Checked,
+}
+
+/// Parses an overflow policy name as given with `--overflow=`
+fn parse_overflow_policy(name: &str) -> Option<OverflowPolicy> {
+    match name {
+        "wrapping" => Some(OverflowPolicy::Wrapping),
+        "saturating" => Some(OverflowPolicy::Saturating),
+        "checked" => Some(OverflowPolicy::Checked),
+        _ => None,
+    }
+}
+
+/// Return the total fuel for all the masses, adding it up with the policy
+fn sum_fuel(masses: &[i64], policy: OverflowPolicy) -> Result<i64, common::AocError> {
+    let mut total: i64 = 0;
+    for &mass in masses {
+        let fuel = get_wide_fuel(mass);
+        total = match policy {
+            OverflowPolicy::Wrapping => total.wrapping_add(fuel),
+            OverflowPolicy::Saturating => total.saturating_add(fuel),
+            OverflowPolicy::Checked => total.checked_add(fuel).ok_or(common::AocError::Overflow)?,
+        };
+    }
+    Ok(total)
+}
+
+/// Return how many positive fuel amounts get added up for this mass in
+/// get_fuel.
+#[cfg_attr(not(test), allow(dead_code))]
+fn fuel_steps(mass: i32) -> usize {
+    let mut steps = 0;
+    let mut fuel = (mass / 3) - 2;
+    while fuel > 0 {
+        steps += 1;
+        fuel = (fuel / 3) - 2;
+    }
+    steps
+}
+
+/// Return whether the fuel for this mass needs fuel of its own, so part 2
+/// adds something to part 1
+#[cfg_attr(not(test), allow(dead_code))]
+fn needs_recursion(mass: i32) -> bool {
+    get_simple_fuel(get_simple_fuel(mass)) > 0
+}
+
+/// Return how many of the modules need fuel for their fuel
+#[cfg_attr(not(test), allow(dead_code))]
+fn count_needing_recursion(masses: &[i32]) -> usize {
+    masses.iter().filter(|&&mass| needs_recursion(mass)).count()
+}
+
+/// Return each module's mass paired with the fuel it requires
+#[cfg_attr(not(test), allow(dead_code))]
+fn per_module_fuel(masses: &[i32]) -> Vec<(i32, i32)> {
+    masses.iter().map(|&mass| (mass, get_fuel(mass))).collect()
+}
+
+/// Return the (mass, fuel) of the module that needs the most fuel; the first
+/// one wins a tie
+#[cfg_attr(not(test), allow(dead_code))]
+fn max_module_fuel(masses: &[i32]) -> Option<(i32, i32)> {
+    per_module_fuel(masses)
+        .into_iter()
+        .rev()
+        .max_by_key(|&(_, fuel)| fuel)
+}
+
+/// Return (lower, upper, count) for equal-width buckets over the range of the
+/// fuel values; bounds are inclusive and there are at most `buckets` buckets
+fn fuel_histogram(fuels: &[i64], buckets: usize) -> Vec<(i64, i64, usize)> {
+    let (low, high) = match (fuels.iter().min(), fuels.iter().max()) {
+        (Some(&low), Some(&high)) if buckets > 0 => (i128::from(low), i128::from(high)),
+        _ => return vec![],
+    };
+
+    // Round the width up so the buckets cover the whole range
+    let span = high - low + 1;
+    let width = (span + buckets as i128 - 1) / buckets as i128;
+
+    let mut histogram = Vec::new();
+    let mut lower = low;
+    while lower <= high {
+        let upper = (lower + width - 1).min(high);
+        let count = fuels
+            .iter()
+            .filter(|&&fuel| lower <= i128::from(fuel) && i128::from(fuel) <= upper)
+            .count();
+        histogram.push((lower as i64, upper as i64, count));
+        lower += width;
+    }
+    histogram
+}
+
+/// Parses line `line_num` (starting at 1) of the puzzle input into masses
+/// separated by commas or whitespace; items that aren't integers of type `T`
+/// are skipped with a warning
+fn parse_mass_line<T: FromStr>(line_num: usize, line: &str) -> impl Iterator<Item = T> + '_ {
+    line.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|item| !item.is_empty())
+        .filter_map(move |item| match item.parse::<T>() {
+            Ok(mass) => Some(mass),
+            Err(_) => {
+                eprintln!("Warning: line {}: skipping {:?}", line_num, item);
+                None
+            }
+        })
+}
+
+/// Parses the puzzle input into masses separated by newlines, commas, or any
+/// other whitespace; items that aren't integers are skipped with a warning
+#[cfg_attr(not(test), allow(dead_code))]
+fn parse_masses(input: &str) -> Vec<i64> {
+    input
+        .lines()
+        .enumerate()
+        .flat_map(|(line_num, line)| parse_mass_line(line_num + 1, line))
+        .collect()
+}
+
+/// Return the total fuel for the masses read from the reader a line at a
+/// time, split up like parse_masses. Read errors come back as io errors;
+/// items that aren't integers are skipped with a warning.
+#[cfg_attr(not(test), allow(dead_code))]
+fn sum_fuel_from_reader<R: BufRead>(reader: R) -> io::Result<i64> {
+    solve_from_reader(reader, |_, _| {}).map(|(_, total)| total)
+}
+
+/// Like sum_fuel_from_reader, but calls `visit` with each module's mass and
+/// fuel and returns the part 1 and part 2 totals. Masses are read as i64 and
+/// the totals wrap if they overflow; sum_fuel adds them up another way.
+fn solve_from_reader<R, F>(reader: R, mut visit: F) -> io::Result<(i64, i64)>
+where
+    R: BufRead,
+    F: FnMut(i64, i64),
+{
+    let mut simple_total: i64 = 0;
+    let mut total: i64 = 0;
+    for (line_num, line) in reader.lines().enumerate() {
+        let line = line?;
+        for mass in parse_mass_line(line_num + 1, &line) {
+            let fuel = get_wide_fuel(mass);
+            simple_total = simple_total.wrapping_add(get_wide_simple_fuel(mass));
+            total = total.wrapping_add(fuel);
+            visit(mass, fuel);
+        }
+    }
+    Ok((simple_total, total))
+}
+
+/// Like sum_fuel_from_reader, but calls `report` with the number of modules
+/// and the total so far after every `every` modules; 0 never reports
+#[cfg_attr(not(test), allow(dead_code))]
+fn sum_fuel_with_subtotals<R, F>(reader: R, every: usize, report: F) -> io::Result<i64>
+where
+    R: BufRead,
+    F: FnMut(usize, i64),
+{
+    let mut add_subtotal = subtotal_reporter(every, report);
+    solve_from_reader(reader, |_, fuel| add_subtotal(fuel)).map(|(_, total)| total)
+}
+
+/// Return a function to call with each module's fuel that calls `report`
+/// with the number of modules and the total so far after every `every`
+/// modules; 0 never reports
+fn subtotal_reporter<F: FnMut(usize, i64)>(every: usize, mut report: F) -> impl FnMut(i64) {
+    let mut subtotal: i64 = 0;
+    let mut count = 0;
+    move |fuel| {
+        subtotal = subtotal.wrapping_add(fuel);
+        count += 1;
+        if every > 0 && count % every == 0 {
+            report(count, subtotal);
+        }
+    }
+}
+
+/// Day 1 solution; the input is the module masses, one per line
+#[cfg_attr(not(test), allow(dead_code))]
+struct Day1;
+
+impl common::Solution for Day1 {
+    fn part1(&self, input: &str) -> String {
+        let (simple_total, _) = solve(&parse_masses(input));
+        simple_total.to_string()
+    }
+
+    fn part2(&self, input: &str) -> String {
+        let (_, total) = solve(&parse_masses(input));
+        total.to_string()
+    }
+}
+
+/// Write the totals; as JSON with `json`. Unless `json` or `quiet` is on,
+/// the part 2 total comes after a table of each module's fuel, and
+/// `verbose` adds a histogram of the fuel values.
+fn write_report<W: Write>(
+    out: &mut W,
+    modules: &[(i64, i64)],
+    (simple_total, total): (Option<i64>, Option<i64>),
+    json: bool,
+    quiet: bool,
+    verbose: bool,
+) -> io::Result<()> {
+    if json {
+        return writeln!(out, "{}", common::format_json_parts(simple_total, total));
+    }
+
+    // Show the fuel for each module that went into part 2
+    if total.is_some() && !quiet {
+        writeln!(out, "{:>10} {:>10}", "mass", "fuel")?;
+        for (mass, fuel) in modules.iter() {
+            writeln!(out, "{:>10} {:>10}", mass, fuel)?;
+        }
+
+        if verbose {
+            writeln!(out, "{:>10} {:>10} {:>6}", "lower", "upper", "count")?;
+            let fuel_values: Vec<i64> = modules.iter().map(|&(_, fuel)| fuel).collect();
+            for (lower, upper, count) in fuel_histogram(&fuel_values, 10) {
+                writeln!(out, "{:>10} {:>10} {:>6}", lower, upper, count)?;
+            }
+        }
+    }
+
+    if let Some(simple_total) = simple_total {
+        writeln!(out, "simple total: {}", simple_total)?;
+    }
+    if let Some(total) = total {
+        writeln!(out, "total: {}", total)?;
+    }
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let json = common::wants_json(&args);
+    let verbose = common::has_flag(&args, "--verbose");
+    let quiet = common::has_flag(&args, "--quiet");
+    let policy = match common::flag_values(&args, "--overflow").last() {
+        None => None,
+        Some(name) => match parse_overflow_policy(name) {
+            Some(policy) => Some(policy),
+            None => {
+                let e = common::AocError::Usage(
+                    "--overflow must be wrapping, saturating, or checked".to_string(),
+                );
+                eprintln!("Error: {}.", e);
+                std::process::exit(common::exit_code_for(&e));
+            }
+        },
+    };
+    let part = match common::selected_part(&args) {
+        Ok(part) => part,
+        Err(e) => {
+            eprintln!("Error: {}.", e);
+            std::process::exit(common::exit_code_for(&e));
+        }
+    };
+
+    // With --subtotal-every=N, print the part 2 total so far every N modules
+    // while the input streams in; the subtotals are plain text and only
+    // cover part 2
+    let every = match common::flag_values(&args, "--subtotal-every").last() {
+        None => 0,
+        Some(every) => {
+            let problem = match every.parse::<usize>() {
+                Ok(every) if every > 0 => None,
+                _ => Some("--subtotal-every must be a positive number"),
+            }
+            .or_else(|| json.then_some("--subtotal-every can't be used with --json"))
+            .or_else(|| (!part.part2()).then_some("--subtotal-every needs part 2"));
+            if let Some(problem) = problem {
+                let e = common::AocError::Usage(problem.to_string());
+                eprintln!("Error: {}.", e);
+                std::process::exit(common::exit_code_for(&e));
+            }
+            every.parse::<usize>().unwrap_or(0)
+        }
+    };
+
+    // The first arg is the data file path; read stdin if there isn't one
+    let datafile_arg = common::first_positional(&args);
+    if let Some(path) = datafile_arg {
+        if !json && !quiet {
+            println!("Using datafile: {}", Path::new(path).display());
+        }
+    }
+
+    // Stream the data file, getting both totals and each module's fuel in one
+    // pass; with --overflow the part 2 total is added up again with that
+    // policy
+    let mut modules = Vec::new();
+    let mut add_subtotal = subtotal_reporter(every, |count, subtotal| {
+        println!("subtotal after {} modules: {}", count, subtotal);
+    });
+    let totals = common::open_input(datafile_arg).and_then(|reader| {
+        solve_from_reader(reader, |mass, fuel| {
+            modules.push((mass, fuel));
+            add_subtotal(fuel);
+        })
+    });
+    let (simple_total, mut total) = match totals {
+        Err(e) => {
+            eprintln!("Can't open file. {}", e);
+            std::process::exit(common::exit_code_for(&e.into()));
+        }
+        Ok(totals) => totals,
+    };
+    if let Some(policy) = policy {
+        let wide_masses: Vec<i64> = modules.iter().map(|&(mass, _)| mass).collect();
+        total = match sum_fuel(&wide_masses, policy) {
+            Ok(total) => total,
+            Err(e) => {
+                eprintln!("Can't add up the fuel: {}", e);
+                std::process::exit(common::exit_code_for(&e));
+            }
+        };
+    }
+    let simple_total = part.part1().then_some(simple_total);
+    let total = part.part2().then_some(total);
+
+    let stdout = io::stdout();
+    if let Err(e) = write_report(
+        &mut stdout.lock(),
+        &modules,
+        (simple_total, total),
+        json,
+        quiet,
+        verbose,
+    ) {
+        eprintln!("Can't write output: {}", e);
+        std::process::exit(common::exit_code_for(&e.into()));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::Solution;
+
+    const EXAMPLE: &str = "12\n14\n1969\n100756\n";
+
+    #[test]
+    fn test_get_simple_fuel() {
+        assert_eq!(get_simple_fuel(0), 0);
+        assert_eq!(get_simple_fuel(12), 2);
+        assert_eq!(get_simple_fuel(14), 2);
+        assert_eq!(get_simple_fuel(1969), 654);
+        assert_eq!(get_simple_fuel(100756), 33583);
+    }
+
+    #[test]
+    fn test_per_module_fuel() {
+        assert_eq!(
+            per_module_fuel(&[12, 14, 1969, 100756]),
+            vec![(12, 2), (14, 2), (1969, 966), (100756, 50346)]
+        );
+        assert_eq!(per_module_fuel(&[]), vec![]);
+    }
+
+    #[test]
+    fn test_needs_recursion() {
+        assert!(!needs_recursion(0));
+        assert!(!needs_recursion(12));
+        assert!(!needs_recursion(14));
+        assert!(!needs_recursion(32));
+        assert!(needs_recursion(33));
+        assert!(needs_recursion(1969));
+        assert!(needs_recursion(100756));
+        for mass in 0..200 {
+            assert_eq!(
+                needs_recursion(mass),
+                get_fuel(mass) > get_simple_fuel(mass)
+            );
+        }
+    }
+
+    #[test]
+    fn test_count_needing_recursion() {
+        assert_eq!(count_needing_recursion(&[12, 14, 1969, 100756]), 2);
+        assert_eq!(count_needing_recursion(&[12, 32]), 0);
+        assert_eq!(count_needing_recursion(&[]), 0);
+    }
+
+    #[test]
+    fn test_max_module_fuel() {
+        assert_eq!(
+            max_module_fuel(&[12, 100756, 14, 1969]),
+            Some((100756, 50346))
+        );
+        assert_eq!(max_module_fuel(&[14, 12]), Some((14, 2)));
+        assert_eq!(max_module_fuel(&[]), None);
+    }
+
+    #[test]
+    fn test_get_wide_simple_fuel() {
+        assert_eq!(get_wide_simple_fuel(0), 0);
+        assert_eq!(get_wide_simple_fuel(100756), 33583);
+        assert_eq!(get_wide_simple_fuel(30_000_000_000), 9_999_999_998);
+    }
 
     #[test]
     fn test_get_fuel() {
@@ -28,47 +493,315 @@ mod tests {
         assert_eq!(get_fuel(12), 2);
         assert_eq!(get_fuel(100), 39);
     }
-}
 
-fn main() {
-    // The first arg is the data file path
-    let datafile_arg = match std::env::args().nth(1) {
-        Some(path) => path,
-        None => {
-            eprintln!("Error: no textfile provided.");
-            std::process::exit(exitcode::DATAERR);
+    #[test]
+    fn test_get_fuel_with() {
+        for mass in [12, 14, 1969, 100756] {
+            assert_eq!(get_fuel_with(mass, 3, 2), i64::from(get_fuel(mass as i32)));
         }
-    };
 
-    let path = Path::new(&datafile_arg);
+        // 100 -> 24 -> 5 -> 0
+        assert_eq!(get_fuel_with(100, 4, 1), 29);
+        assert_eq!(get_fuel_with(3, 4, 1), 0);
+        // No offset still runs out once the division gets to 0
+        assert_eq!(get_fuel_with(8, 2, 0), 7);
+    }
 
-    println!("Using datafile: {}", path.display());
+    #[test]
+    #[should_panic(expected = "fuel never runs out")]
+    fn test_get_fuel_with_divisor_1() {
+        get_fuel_with(100, 1, 2);
+    }
 
-    // Open the data file
-    let file = match File::open(&path) {
-        Err(e) => {
-            eprintln!("Can't open file. {}", e);
-            std::process::exit(exitcode::DATAERR);
+    #[test]
+    #[should_panic(expected = "fuel never runs out")]
+    fn test_get_fuel_with_divisor_0() {
+        get_fuel_with(100, 0, 2);
+    }
+
+    #[test]
+    fn test_get_wide_fuel() {
+        assert_eq!(get_wide_fuel(100756), i64::from(get_fuel(100756)));
+        assert!(get_wide_fuel(i64::MAX) > i64::MAX / 3);
+    }
+
+    #[test]
+    fn test_solve() {
+        let masses = vec![12, 14, 1969, 100756];
+        assert_eq!(solve(&masses), (34241, 51316));
+
+        let simple_total: i32 = masses
+            .iter()
+            .map(|&mass| get_simple_fuel(mass as i32))
+            .sum();
+        let total: i32 = masses.iter().map(|&mass| get_fuel(mass as i32)).sum();
+        assert_eq!(solve(&masses), (i64::from(simple_total), i64::from(total)));
+
+        assert_eq!(solve(&[]), (0, 0));
+        assert_eq!(solve(&[1, 8]), (0, 0));
+    }
+
+    #[test]
+    fn test_sum_fuel_parallel() {
+        assert_eq!(sum_fuel_parallel(&[12, 14, 1969, 100756]), 51316);
+        assert_eq!(sum_fuel_parallel(&[]), 0);
+
+        let masses: Vec<i64> = (0..100_000).map(|i| (i * 7919) % 1_000_000).collect();
+        let total: i64 = masses.iter().map(|&mass| get_wide_fuel(mass)).sum();
+        assert_eq!(sum_fuel_parallel(&masses), total);
+    }
+
+    #[test]
+    fn test_sum_fuel_simple() {
+        let masses = vec![12, 14, 1969, 100756];
+        assert_eq!(sum_fuel_simple(&masses), 34241);
+        assert_eq!(sum_fuel_simple(&masses), solve(&masses).0);
+        assert_ne!(
+            sum_fuel_simple(&masses),
+            sum_fuel(&masses, OverflowPolicy::Checked).unwrap()
+        );
+
+        // Small masses need no fuel rather than negative fuel
+        assert_eq!(sum_fuel_simple(&[1, 5, 8, 9]), 1);
+        assert_eq!(sum_fuel_simple(&[]), 0);
+    }
+
+    #[test]
+    fn test_parse_overflow_policy() {
+        assert_eq!(
+            parse_overflow_policy("wrapping"),
+            Some(OverflowPolicy::Wrapping)
+        );
+        assert_eq!(
+            parse_overflow_policy("saturating"),
+            Some(OverflowPolicy::Saturating)
+        );
+        assert_eq!(
+            parse_overflow_policy("checked"),
+            Some(OverflowPolicy::Checked)
+        );
+        assert_eq!(parse_overflow_policy("clamp"), None);
+    }
+
+    #[test]
+    fn test_sum_fuel() {
+        let masses = vec![12, 14, 1969, 100756];
+        for policy in [
+            OverflowPolicy::Wrapping,
+            OverflowPolicy::Saturating,
+            OverflowPolicy::Checked,
+        ] {
+            assert_eq!(sum_fuel(&masses, policy), Ok(51316));
         }
-        Ok(file) => file,
-    };
+    }
 
-    // Iterate over the items in the data file converting each line
-    // to an int, doing the silly math, and accumulating it
-    let mut total = 0;
-    let lines = io::BufReader::new(file).lines();
-    for line in lines {
-        match line {
-            Err(_) => (),
-            Ok(data) => {
-                let data_i = data.parse::<i32>().unwrap();
-                let fuel = get_fuel(data_i);
-                println!("{} -> {}", data_i, fuel);
-                total += fuel;
-            }
+    #[test]
+    fn test_sum_fuel_overflow() {
+        let fuel = get_wide_fuel(i64::MAX);
+        let masses = vec![i64::MAX; 3];
+
+        assert_eq!(
+            sum_fuel(&masses, OverflowPolicy::Wrapping),
+            Ok(fuel.wrapping_add(fuel).wrapping_add(fuel))
+        );
+        assert_eq!(sum_fuel(&masses, OverflowPolicy::Saturating), Ok(i64::MAX));
+        assert_eq!(
+            sum_fuel(&masses, OverflowPolicy::Checked),
+            Err(common::AocError::Overflow)
+        );
+
+        // Right up to the edge is fine
+        assert_eq!(sum_fuel(&masses[..1], OverflowPolicy::Checked), Ok(fuel));
+    }
+
+    #[test]
+    fn test_get_fuel_monotonic() {
+        // Every mass through the zero-fuel threshold and the first few steps
+        // of the recursion
+        let masses: Vec<i32> = (-3..2000).collect();
+        for pair in masses.windows(2) {
+            assert!(get_fuel(pair[0]) <= get_fuel(pair[1]), "{:?}", pair);
+            assert!(
+                get_simple_fuel(pair[0]) <= get_simple_fuel(pair[1]),
+                "{:?}",
+                pair
+            );
+        }
+
+        // Spread-out masses from a simple linear congruential generator
+        let mut state: u32 = 2019;
+        let mut masses: Vec<i32> = (0..1000)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (state >> 8) as i32
+            })
+            .collect();
+        masses.sort_unstable();
+        for pair in masses.windows(2) {
+            assert!(get_fuel(pair[0]) <= get_fuel(pair[1]), "{:?}", pair);
+        }
+    }
+
+    #[test]
+    fn test_fuel_steps() {
+        assert_eq!(fuel_steps(0), 0);
+        assert_eq!(fuel_steps(8), 0);
+        assert_eq!(fuel_steps(14), 1);
+        assert_eq!(fuel_steps(1969), 5);
+        assert_eq!(fuel_steps(100756), 9);
+    }
+
+    #[test]
+    fn test_fuel_histogram() {
+        let fuels = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+        assert_eq!(fuel_histogram(&fuels, 2), vec![(1, 5, 5), (6, 10, 5)]);
+        assert_eq!(
+            fuel_histogram(&fuels, 3),
+            vec![(1, 4, 4), (5, 8, 4), (9, 10, 2)]
+        );
+
+        let fuels = vec![2, 2, 966, 50346];
+        assert_eq!(
+            fuel_histogram(&fuels, 4),
+            vec![
+                (2, 12588, 3),
+                (12589, 25175, 0),
+                (25176, 37762, 0),
+                (37763, 50346, 1)
+            ]
+        );
+
+        // Fewer distinct values than buckets
+        assert_eq!(fuel_histogram(&[5, 5], 3), vec![(5, 5, 2)]);
+
+        assert_eq!(fuel_histogram(&[], 3), vec![]);
+        assert_eq!(fuel_histogram(&[1, 2], 0), vec![]);
+    }
+
+    #[test]
+    fn test_parse_masses() {
+        assert_eq!(parse_masses(EXAMPLE), vec![12, 14, 1969, 100756]);
+        assert_eq!(parse_masses("12\r\n\n14"), vec![12, 14]);
+    }
+
+    #[test]
+    fn test_parse_masses_delimiters() {
+        let masses = vec![12, 14, 1969, 100756];
+        assert_eq!(parse_masses("12,14,1969,100756\n"), masses);
+        assert_eq!(parse_masses("12, 14,\t1969\n100756"), masses);
+        assert_eq!(parse_masses("12,\n14 ,,1969\r\n 100756,"), masses);
+
+        assert_eq!(Day1.part2("12,14,1969,100756"), Day1.part2(EXAMPLE));
+        assert_eq!(Day1.part2("12 14\n1969,100756"), Day1.part2(EXAMPLE));
+    }
+
+    #[test]
+    fn test_sum_fuel_from_reader() {
+        let reader = io::Cursor::new(b"12\n\n14\r\n1969\n100756\n");
+        assert_eq!(sum_fuel_from_reader(reader).unwrap(), 51316);
+
+        assert_eq!(sum_fuel_from_reader(io::Cursor::new(b"")).unwrap(), 0);
+
+        let reader = io::Cursor::new(b"12\nabc\n14,1969 100756\n");
+        assert_eq!(sum_fuel_from_reader(reader).unwrap(), 51316);
+
+        // Bytes that aren't UTF-8 are a read error
+        let err = sum_fuel_from_reader(io::Cursor::new(b"12\n\xff\n")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_solve_from_reader() {
+        let mut modules = Vec::new();
+        let reader = io::Cursor::new(EXAMPLE);
+        let totals = solve_from_reader(reader, |mass, fuel| modules.push((mass, fuel)));
+        assert_eq!(totals.unwrap(), (34241, 51316));
+        assert_eq!(
+            modules,
+            vec![(12, 2), (14, 2), (1969, 966), (100756, 50346)]
+        );
+
+        let reader = io::Cursor::new(b"12,abc\n\n14\n");
+        assert_eq!(solve_from_reader(reader, |_, _| {}).unwrap(), (4, 4));
+
+        // Masses too big for an i32 still count
+        let reader = io::Cursor::new(b"3000000000\n");
+        assert_eq!(
+            solve_from_reader(reader, |_, _| {}).unwrap(),
+            (999_999_998, get_wide_fuel(3_000_000_000))
+        );
+    }
+
+    #[test]
+    fn test_parse_masses_bad_lines() {
+        let input = "12\nabc\n14\n1.5\n1969, x\n100756\n";
+        assert_eq!(parse_masses(input), vec![12, 14, 1969, 100756]);
+        assert_eq!(Day1.part2(input), Day1.part2(EXAMPLE));
+
+        assert_eq!(parse_masses("nope\n"), vec![]);
+    }
+
+    #[test]
+    fn test_quiet_flag() {
+        // --quiet drops the per-module table; the totals are the same
+        let modules = vec![(12, 2), (14, 2), (1969, 966), (100756, 50346)];
+        let report = |quiet| {
+            let mut out = Vec::new();
+            write_report(
+                &mut out,
+                &modules,
+                (Some(34241), Some(51316)),
+                false,
+                quiet,
+                false,
+            )
+            .unwrap();
+            String::from_utf8(out).unwrap()
         };
+
+        let full = report(false);
+        assert!(full.contains("        12          2\n"));
+        assert!(full.contains("    100756      50346\n"));
+        assert!(full.ends_with("simple total: 34241\ntotal: 51316\n"));
+
+        assert_eq!(report(true), "simple total: 34241\ntotal: 51316\n");
     }
 
-    // Print the total
-    println!("total: {}", total);
+    #[test]
+    fn test_sum_fuel_with_subtotals() {
+        let mut subtotals = Vec::new();
+        let reader = io::Cursor::new(b"12\n14\n\n1969\n100756\n12\n");
+        let total = sum_fuel_with_subtotals(reader, 2, |count, subtotal| {
+            subtotals.push((count, subtotal));
+        });
+        assert_eq!(total.unwrap(), 51318);
+        assert_eq!(subtotals, vec![(2, 4), (4, 51316)]);
+
+        // A bad line is skipped and doesn't count as a module
+        let mut subtotals = Vec::new();
+        let reader = io::Cursor::new(b"12\nabc\n14\n1969\n");
+        let total = sum_fuel_with_subtotals(reader, 1, |count, subtotal| {
+            subtotals.push((count, subtotal));
+        });
+        assert_eq!(total.unwrap(), 970);
+        assert_eq!(subtotals, vec![(1, 2), (2, 4), (3, 970)]);
+
+        let mut calls = 0;
+        let total = sum_fuel_with_subtotals(io::Cursor::new(EXAMPLE), 0, |_, _| calls += 1);
+        assert_eq!(total.unwrap(), 51316);
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    fn test_solution() {
+        assert_eq!(Day1.part1(EXAMPLE), "34241");
+        assert_eq!(Day1.part2(EXAMPLE), "51316");
+
+        // Past what fits in an i32
+        assert_eq!(Day1.part1("9000000000"), "2999999998");
+        assert_eq!(
+            Day1.part2("9000000000"),
+            get_wide_fuel(9_000_000_000).to_string()
+        );
+    }
 }
@@ -1,13 +1,30 @@
 // This Source Code Form is subject to the terms of the Mozilla Public
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
-//
-use std::fs::File;
-use std::io::prelude::*;
-use std::path::Path;
+
+use std::collections::{HashMap, VecDeque};
+use std::convert::TryFrom;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Split an instruction into its opcode and the three parameter modes
+fn decode(instr: i64) -> (i64, [u8; 3]) {
+    let opcode = instr % 100;
+    let modes = [
+        ((instr / 100) % 10) as u8,
+        ((instr / 1000) % 10) as u8,
+        ((instr / 10000) % 10) as u8,
+    ];
+    (opcode, modes)
+}
+
+/// Parse comma-separated program text for the day 2 interpreter
+fn parse_usize_program(contents: &str) -> Result<Vec<usize>, common::AocError> {
+    common::parse_list::<usize>(contents, ',')
+}
 
 /// Run the given program and return the output
-fn run_program(data: &Vec<usize>) -> Vec<usize> {
+fn run_program(data: &[usize]) -> Vec<usize> {
     // Copy the vector
     let mut data_out = data.to_vec();
 
@@ -18,28 +35,43 @@ fn run_program(data: &Vec<usize>) -> Vec<usize> {
             break;
         }
 
-        let opcode = data_out[i];
+        // Day 2 programs only use position mode
+        let (opcode, modes) = decode(data_out[i] as i64);
+        if modes != [0; 3] {
+            let e = common::AocError::Data(format!(
+                "unsupported parameter mode in {} at {}; day 2 only has position mode",
+                data_out[i], i
+            ));
+            eprintln!("Error: {}", e);
+            std::process::exit(common::exit_code_for(&e));
+        }
         match opcode {
             1 => {
                 let lhs_i = data_out[i + 1];
                 let rhs_i = data_out[i + 2];
                 let dest_i = data_out[i + 3];
-                data_out[dest_i] = data_out[lhs_i] + data_out[rhs_i];
+                data_out[dest_i] = data_out[lhs_i].wrapping_add(data_out[rhs_i]);
                 i += 4;
             }
             2 => {
                 let lhs_i = data_out[i + 1];
                 let rhs_i = data_out[i + 2];
                 let dest_i = data_out[i + 3];
-                data_out[dest_i] = data_out[lhs_i] * data_out[rhs_i];
+                data_out[dest_i] = data_out[lhs_i].wrapping_mul(data_out[rhs_i]);
                 i += 4;
             }
             99 => {
                 break;
             }
+            0 => {
+                let e = common::AocError::Data(format!("ran into a 0 at {}; is it data?", i));
+                eprintln!("Error: {}", e);
+                std::process::exit(common::exit_code_for(&e));
+            }
             _ => {
-                eprintln!("Unknown operand: {}", data_out[i]);
-                std::process::exit(exitcode::DATAERR);
+                let e = common::AocError::Data(format!("unknown operand: {}", data_out[i]));
+                eprintln!("Error: {}", e);
+                std::process::exit(common::exit_code_for(&e));
             }
         }
     }
@@ -47,84 +79,2172 @@ fn run_program(data: &Vec<usize>) -> Vec<usize> {
     data_out
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Run a copy of the program with the noun and verb patched into positions 1
+/// and 2 and return position 0
+fn run_with_noun_verb(program: &[usize], noun: usize, verb: usize) -> usize {
+    let mut program = program.to_vec();
+    program[1] = noun;
+    program[2] = verb;
+    run_program(&program)[0]
+}
 
-    /// Test 1 oper
-    #[test]
-    fn test_run_program_oper_1() {
-        assert_eq!(run_program(&vec![1, 0, 0, 0, 99]), vec![2, 0, 0, 0, 99]);
+/// Return the first (noun, verb) pair that makes the program produce the target
+fn find_noun_verb(program: &[usize], target: usize) -> Option<(usize, usize)> {
+    for noun in 0..=99 {
+        for verb in 0..=99 {
+            if run_with_noun_verb(program, noun, verb) == target {
+                return Some((noun, verb));
+            }
+        }
     }
+    None
+}
 
-    /// Test 2 oper
-    #[test]
-    fn test_run_program_oper_2() {
-        assert_eq!(run_program(&vec![2, 3, 0, 3, 99]), vec![2, 3, 0, 6, 99]);
+/// Return every (noun, verb) pair that makes the program produce the target,
+/// in the order find_noun_verb tries them
+#[cfg_attr(not(test), allow(dead_code))]
+fn all_noun_verb(program: &[usize], target: usize) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    for noun in 0..=99 {
+        for verb in 0..=99 {
+            if run_with_noun_verb(program, noun, verb) == target {
+                pairs.push((noun, verb));
+            }
+        }
     }
+    pairs
+}
 
-    /// Test complex
-    #[test]
-    fn test_run_program_complex() {
-        assert_eq!(
-            run_program(&vec![1, 1, 1, 4, 99, 5, 6, 0, 99]),
-            vec![30, 1, 1, 4, 2, 5, 6, 0, 99]
-        );
+/// Errors from parsing or running an intcode program
+#[derive(Debug, PartialEq)]
+enum IntcodeError {
+    /// A value in the program text isn't an integer
+    Parse(String),
+    /// The instruction at `ip` has an opcode we don't know
+    UnknownOpcode { ip: usize, opcode: i64 },
+    /// The instruction at `ip` is a 0, which usually means running into data
+    HaltedUnexpectedly { ip: usize },
+    /// A parameter points at a negative address or past `MAX_MEMORY`
+    InvalidAddress(i64),
+    /// An input instruction ran with nothing to read
+    NoInput,
+    /// The nth parameter (starting at 1) of the instruction at `ip` is written
+    /// to but is in immediate mode
+    InvalidWriteMode { ip: usize, param: usize },
+    /// A diagnostic program printed a nonzero test result before its code
+    #[cfg_attr(not(test), allow(dead_code))]
+    SelfTestFailed(i64),
+    /// The program halted without printing anything
+    #[cfg_attr(not(test), allow(dead_code))]
+    NoOutput,
+    /// A line of assembly source isn't a valid instruction
+    #[cfg_attr(not(test), allow(dead_code))]
+    InvalidAssembly(String),
+    /// Saved machine state is truncated or malformed
+    #[cfg_attr(not(test), allow(dead_code))]
+    InvalidState,
+}
+
+impl fmt::Display for IntcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IntcodeError::Parse(val) => write!(f, "can't parse value: {:?}", val),
+            IntcodeError::UnknownOpcode { ip, opcode } => {
+                write!(f, "unknown opcode {} at {}", opcode, ip)
+            }
+            IntcodeError::HaltedUnexpectedly { ip } => {
+                write!(f, "ran into a 0 at {}; is it data?", ip)
+            }
+            IntcodeError::InvalidAddress(addr) => write!(f, "invalid address: {}", addr),
+            IntcodeError::NoInput => write!(f, "no input available"),
+            IntcodeError::InvalidWriteMode { ip, param } => {
+                write!(
+                    f,
+                    "parameter {} at {} is written to in immediate mode",
+                    param, ip
+                )
+            }
+            IntcodeError::SelfTestFailed(value) => write!(f, "self-test failed: {}", value),
+            IntcodeError::NoOutput => write!(f, "no output"),
+            IntcodeError::InvalidAssembly(line) => write!(f, "can't assemble {:?}", line),
+            IntcodeError::InvalidState => write!(f, "saved state isn't valid"),
+        }
     }
 }
 
-fn main() {
-    // First arg is the data file path
-    let datafile_arg = match std::env::args().nth(1) {
-        Some(path) => path,
-        None => {
-            eprintln!("Error: no textfile provided.");
-            std::process::exit(exitcode::DATAERR);
+impl From<IntcodeError> for common::AocError {
+    fn from(err: IntcodeError) -> common::AocError {
+        common::AocError::Data(err.to_string())
+    }
+}
+
+/// Parse comma-separated program text into intcode values
+fn parse_program(program: &str) -> Result<Vec<i64>, IntcodeError> {
+    common::parse_list(program, ',').map_err(|e| match e {
+        common::AocError::Parse { token, .. } => IntcodeError::Parse(token),
+        other => IntcodeError::Parse(other.to_string()),
+    })
+}
+
+/// Highest address a program can use; keeps a bad write from allocating
+/// everything
+const MAX_MEMORY: usize = 1 << 24;
+
+/// How many instructions run_with_timeout runs between clock checks
+const TIMEOUT_CHECK_INTERVAL: u32 = 1024;
+
+/// How long main lets a program run with --input before giving up on it
+const OUTPUT_RUN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Why a machine stopped running
+#[derive(Debug, PartialEq)]
+enum RunState {
+    /// The machine ran its halt instruction
+    Halted,
+    /// The machine is at an input instruction and the input queue is empty
+    NeedsInput,
+    /// The machine was still running when its time ran out
+    TimedOut,
+}
+
+/// A memory write recorded by a machine with its write log on
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct WriteEvent {
+    /// Address of the instruction that did the write
+    ip: usize,
+    addr: usize,
+    old: i64,
+    new: i64,
+}
+
+/// What one instruction run by step_described did
+#[cfg_attr(not(test), allow(dead_code))]
+#[derive(Debug, PartialEq)]
+struct StepInfo {
+    /// Address the instruction ran from
+    ip: usize,
+    /// Assembly name of the instruction, like `ADD`
+    name: &'static str,
+    /// The raw value and mode of each parameter
+    params: Vec<(i64, u8)>,
+    /// The address and value written, if the instruction wrote to memory
+    write: Option<(usize, i64)>,
+}
+
+/// Machine settings for experimenting with intcode variants
+#[derive(Clone, Debug)]
+struct Config {
+    /// The opcode that halts the machine
+    halt_opcode: i64,
+    /// Whether an unknown opcode halts the machine rather than erroring
+    halt_on_unknown: bool,
+    /// Whether to count how many times each opcode runs
+    count_opcodes: bool,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            halt_opcode: 99,
+            halt_on_unknown: false,
+            count_opcodes: false,
         }
-    };
+    }
+}
+
+/// An intcode machine with its memory, instruction pointer, and I/O buffers
+#[derive(Debug)]
+struct Computer {
+    #[cfg_attr(not(test), allow(dead_code))]
+    program: Vec<i64>,
+    memory: Vec<i64>,
+    ip: usize,
+    relative_base: i64,
+    input: VecDeque<i64>,
+    output: Vec<i64>,
+    halted: bool,
+    config: Config,
+    opcode_counts: HashMap<i64, u64>,
+    write_log: Option<Vec<WriteEvent>>,
+    max_ip: usize,
+}
+
+impl Computer {
+    /// Create a new machine with a copy of the program loaded into memory
+    fn new(program: &[i64]) -> Computer {
+        Computer::with_config(program, Config::default())
+    }
+
+    /// Create a new machine with a copy of the program loaded into memory and
+    /// the input queue filled with the given values
+    fn with_input(program: &[i64], input: &[i64]) -> Computer {
+        let mut computer = Computer::new(program);
+        computer.input.extend(input);
+        computer
+    }
+
+    /// Create a new machine with a copy of the program loaded into memory and
+    /// the given settings
+    fn with_config(program: &[i64], config: Config) -> Computer {
+        Computer {
+            program: program.to_vec(),
+            memory: program.to_vec(),
+            ip: 0,
+            relative_base: 0,
+            input: VecDeque::new(),
+            output: Vec::new(),
+            halted: false,
+            config,
+            opcode_counts: HashMap::new(),
+            write_log: None,
+            max_ip: 0,
+        }
+    }
+
+    /// Return the value at the given address; memory past the end of the
+    /// program reads as 0
+    fn read(&self, addr: i64) -> Result<i64, IntcodeError> {
+        if addr < 0 || addr as usize >= MAX_MEMORY {
+            return Err(IntcodeError::InvalidAddress(addr));
+        }
+        Ok(self.memory.get(addr as usize).copied().unwrap_or(0))
+    }
+
+    /// Store a value at the given address, growing memory to fit it
+    fn write(&mut self, addr: i64, value: i64) -> Result<(), IntcodeError> {
+        if addr < 0 || addr as usize >= MAX_MEMORY {
+            return Err(IntcodeError::InvalidAddress(addr));
+        }
+        let addr = addr as usize;
+        if addr >= self.memory.len() {
+            self.memory.resize(addr + 1, 0);
+        }
+        if let Some(log) = &mut self.write_log {
+            log.push(WriteEvent {
+                ip: self.ip,
+                addr,
+                old: self.memory[addr],
+                new: value,
+            });
+        }
+        self.memory[addr] = value;
+        Ok(())
+    }
+
+    /// Turn recording every memory write on or off; turning it on starts an
+    /// empty log
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn with_write_log(mut self, on: bool) -> Self {
+        self.write_log = if on { Some(Vec::new()) } else { None };
+        self
+    }
+
+    /// Return the memory writes recorded so far; empty when the write log is
+    /// off
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn write_log(&self) -> &[WriteEvent] {
+        self.write_log.as_deref().unwrap_or(&[])
+    }
+
+    /// Return how many times each opcode has run; only counted when the
+    /// config's `count_opcodes` is on
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn opcode_histogram(&self) -> &HashMap<i64, u64> {
+        &self.opcode_counts
+    }
+
+    /// Return the highest address an instruction has run from
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn max_ip(&self) -> usize {
+        self.max_ip
+    }
+
+    /// Put the machine back to how it was when the program was loaded
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn reset(&mut self) {
+        self.memory = self.program.clone();
+        self.ip = 0;
+        self.relative_base = 0;
+        self.halted = false;
+        self.input.clear();
+        self.output.clear();
+        self.max_ip = 0;
+        self.opcode_counts.clear();
+        if let Some(log) = &mut self.write_log {
+            log.clear();
+        }
+    }
+
+    /// Replace the program with a new one, reusing the memory buffers, and
+    /// put the machine back to its starting state
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn load(&mut self, program: &[i64]) {
+        self.program.clear();
+        self.program.extend_from_slice(program);
+        self.memory.clear();
+        self.memory.extend_from_slice(program);
+        self.ip = 0;
+        self.relative_base = 0;
+        self.halted = false;
+        self.input.clear();
+        self.output.clear();
+        self.max_ip = 0;
+        self.opcode_counts.clear();
+        if let Some(log) = &mut self.write_log {
+            log.clear();
+        }
+    }
+
+    /// Serialize the machine so it can be picked up later with load_state
+    ///
+    /// The halted flag comes first as one byte, then the instruction pointer
+    /// and relative base, then the program, memory, input, and output as a
+    /// count followed by the values. Everything is 8 bytes little-endian.
+    /// The config, opcode counts, write log, and highest instruction pointer
+    /// aren't saved.
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn save_state(&self) -> Vec<u8> {
+        let mut bytes = vec![self.halted as u8];
+        bytes.extend((self.ip as u64).to_le_bytes());
+        bytes.extend(self.relative_base.to_le_bytes());
+        let input: Vec<i64> = self.input.iter().copied().collect();
+        for values in [&self.program, &self.memory, &input, &self.output] {
+            bytes.extend((values.len() as u64).to_le_bytes());
+            for value in values.iter() {
+                bytes.extend(value.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Create a machine from state written by save_state, with the default
+    /// config
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn load_state(bytes: &[u8]) -> Result<Computer, IntcodeError> {
+        let halted = match bytes.first() {
+            Some(0) => false,
+            Some(1) => true,
+            _ => return Err(IntcodeError::InvalidState),
+        };
+        let mut words = bytes[1..].chunks(8).map(|chunk| {
+            <[u8; 8]>::try_from(chunk)
+                .map(i64::from_le_bytes)
+                .map_err(|_| IntcodeError::InvalidState)
+        });
+        let mut next = || words.next().unwrap_or(Err(IntcodeError::InvalidState));
+
+        let ip = next()?;
+        let relative_base = next()?;
+        let mut lists = Vec::new();
+        for _ in 0..4 {
+            let len = next()?;
+            if len < 0 || len as usize > MAX_MEMORY {
+                return Err(IntcodeError::InvalidState);
+            }
+            lists.push((0..len).map(|_| next()).collect::<Result<Vec<i64>, _>>()?);
+        }
+        if next().is_ok() || ip < 0 {
+            return Err(IntcodeError::InvalidState);
+        }
+
+        let output = lists.pop().unwrap_or_default();
+        let input = lists.pop().unwrap_or_default();
+        let memory = lists.pop().unwrap_or_default();
+        let program = lists.pop().unwrap_or_default();
+        let mut computer = Computer::with_input(&program, &input);
+        computer.memory = memory;
+        computer.output = output;
+        computer.ip = ip as usize;
+        computer.relative_base = relative_base;
+        computer.halted = halted;
+        Ok(computer)
+    }
+
+    /// Move the instruction pointer so the next step runs from the address
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn set_ip(&mut self, addr: usize) {
+        self.ip = addr;
+    }
+
+    /// Return the number of memory cells in use; memory only grows, so this is
+    /// also the peak size
+    fn memory_size(&self) -> usize {
+        self.memory.len()
+    }
+
+    /// Return a 64-bit FNV-1a hash of memory, each value as 8 bytes
+    /// little-endian; memory that has grown past the program hashes its extra
+    /// zeros too
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn memory_fingerprint(&self) -> u64 {
+        self.memory
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+            })
+    }
+
+    /// Return the value of the nth parameter (starting at 1) of the current
+    /// instruction
+    fn param(&self, n: usize, mode: u8) -> Result<i64, IntcodeError> {
+        let raw = self.read((self.ip + n) as i64)?;
+        match mode {
+            1 => Ok(raw),
+            2 => self.read(self.relative_base.wrapping_add(raw)),
+            _ => self.read(raw),
+        }
+    }
+
+    /// Return the address the nth parameter (starting at 1) of the current
+    /// instruction writes to; writes can't be in immediate mode
+    fn param_addr(&self, n: usize, mode: u8) -> Result<i64, IntcodeError> {
+        let raw = self.read((self.ip + n) as i64)?;
+        match mode {
+            1 => Err(IntcodeError::InvalidWriteMode {
+                ip: self.ip,
+                param: n,
+            }),
+            2 => Ok(self.relative_base.wrapping_add(raw)),
+            _ => Ok(raw),
+        }
+    }
+
+    /// Execute the instruction at the instruction pointer
+    ///
+    /// Add, multiply, and relative base adjustments wrap on overflow like two's
+    /// complement machine arithmetic; the intcode spec doesn't define overflow
+    /// and no puzzle program relies on it.
+    fn step(&mut self) -> Result<(), IntcodeError> {
+        let (opcode, modes) = decode(self.read(self.ip as i64)?);
+        self.max_ip = self.max_ip.max(self.ip);
+        if self.config.count_opcodes {
+            *self.opcode_counts.entry(opcode).or_insert(0) += 1;
+        }
+        if opcode == self.config.halt_opcode {
+            self.halted = true;
+            return Ok(());
+        }
+
+        match opcode {
+            1 => {
+                let value = self
+                    .param(1, modes[0])?
+                    .wrapping_add(self.param(2, modes[1])?);
+                self.write(self.param_addr(3, modes[2])?, value)?;
+                self.ip += 4;
+            }
+            2 => {
+                let value = self
+                    .param(1, modes[0])?
+                    .wrapping_mul(self.param(2, modes[1])?);
+                self.write(self.param_addr(3, modes[2])?, value)?;
+                self.ip += 4;
+            }
+            3 => {
+                let value = self.input.pop_front().ok_or(IntcodeError::NoInput)?;
+                self.write(self.param_addr(1, modes[0])?, value)?;
+                self.ip += 2;
+            }
+            4 => {
+                let value = self.param(1, modes[0])?;
+                self.output.push(value);
+                self.ip += 2;
+            }
+            5 | 6 => {
+                let value = self.param(1, modes[0])?;
+                let target = self.param(2, modes[1])?;
+                if (opcode == 5) == (value != 0) {
+                    if target < 0 {
+                        return Err(IntcodeError::InvalidAddress(target));
+                    }
+                    self.ip = target as usize;
+                } else {
+                    self.ip += 3;
+                }
+            }
+            7 => {
+                let value = self.param(1, modes[0])? < self.param(2, modes[1])?;
+                self.write(self.param_addr(3, modes[2])?, value as i64)?;
+                self.ip += 4;
+            }
+            8 => {
+                let value = self.param(1, modes[0])? == self.param(2, modes[1])?;
+                self.write(self.param_addr(3, modes[2])?, value as i64)?;
+                self.ip += 4;
+            }
+            9 => {
+                self.relative_base = self.relative_base.wrapping_add(self.param(1, modes[0])?);
+                self.ip += 2;
+            }
+            _ if self.config.halt_on_unknown => {
+                self.halted = true;
+            }
+            // Zeroed memory isn't an instruction; the program ran off into
+            // data or past its end
+            0 => return Err(IntcodeError::HaltedUnexpectedly { ip: self.ip }),
+            _ => {
+                return Err(IntcodeError::UnknownOpcode {
+                    ip: self.ip,
+                    opcode,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Run one instruction like step and describe what it did; an unknown
+    /// opcode that halts the machine is described as `HALT`
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn step_described(&mut self) -> Result<StepInfo, IntcodeError> {
+        let ip = self.ip;
+        let (opcode, modes) = decode(self.read(ip as i64)?);
+        let found = INSTRUCTIONS.iter().find(|&&(code, ..)| code == opcode);
+        let (name, reads, writes) = match found {
+            Some(&(_, name, reads, writes)) if opcode != self.config.halt_opcode => {
+                (name, reads, writes)
+            }
+            _ => ("HALT", 0, false),
+        };
+        let count = reads + writes as usize;
+        let params = (0..count)
+            .map(|n| Ok((self.read((ip + 1 + n) as i64)?, modes[n])))
+            .collect::<Result<Vec<(i64, u8)>, IntcodeError>>()?;
+        let addr = if writes {
+            Some(self.param_addr(count, modes[reads])?)
+        } else {
+            None
+        };
+
+        self.step()?;
+        let write = match addr {
+            Some(addr) => Some((addr as usize, self.read(addr)?)),
+            None => None,
+        };
+        Ok(StepInfo {
+            ip,
+            name,
+            params,
+            write,
+        })
+    }
+
+    /// Run until the machine halts
+    fn run(&mut self) -> Result<(), IntcodeError> {
+        while !self.halted {
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    /// Run until the machine halts or needs input that isn't there yet
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn run_until_input(&mut self) -> Result<RunState, IntcodeError> {
+        loop {
+            if self.halted {
+                return Ok(RunState::Halted);
+            }
+            let (opcode, _) = decode(self.read(self.ip as i64)?);
+            if opcode == 3 && self.input.is_empty() {
+                return Ok(RunState::NeedsInput);
+            }
+            self.step()?;
+        }
+    }
+
+    /// Run until the machine halts or the duration has passed; the clock is
+    /// checked every TIMEOUT_CHECK_INTERVAL instructions, so the run can go a
+    /// little over
+    fn run_with_timeout(&mut self, dur: Duration) -> Result<RunState, IntcodeError> {
+        let start = Instant::now();
+        let mut count = 0;
+        while !self.halted {
+            self.step()?;
+            count += 1;
+            if count == TIMEOUT_CHECK_INTERVAL {
+                if start.elapsed() >= dur {
+                    return Ok(RunState::TimedOut);
+                }
+                count = 0;
+            }
+        }
+        Ok(RunState::Halted)
+    }
+
+    /// Run until the machine outputs a value and return it, taking it off the
+    /// output buffer; returns None if the machine halts first
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn run_until_output(&mut self) -> Result<Option<i64>, IntcodeError> {
+        let len = self.output.len();
+        while !self.halted {
+            self.step()?;
+            if self.output.len() > len {
+                return Ok(self.output.pop());
+            }
+        }
+        Ok(None)
+    }
+
+    /// Add a value to the end of the input queue
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn push_input(&mut self, value: i64) {
+        self.input.push_back(value);
+    }
+
+    /// Return the number of input values waiting to be read
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn input_len(&self) -> usize {
+        self.input.len()
+    }
+
+    /// Return the number of output values produced so far
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn output_len(&self) -> usize {
+        self.output.len()
+    }
+
+    /// Return the output as text, one byte per value, stopping at the first
+    /// value that isn't a byte (like the answer ASCII programs print last);
+    /// bytes that aren't valid UTF-8 come out as replacement characters
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn output_as_string(&self) -> String {
+        let bytes: Vec<u8> = self
+            .output
+            .iter()
+            .map_while(|&value| u8::try_from(value).ok())
+            .collect();
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    /// Take the output three values at a time as (x, y, tile) triples; a
+    /// partial triple at the end is left in the output buffer
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn drain_triples(&mut self) -> Vec<(i64, i64, i64)> {
+        let end = self.output.len() - self.output.len() % 3;
+        self.output
+            .drain(..end)
+            .collect::<Vec<i64>>()
+            .chunks(3)
+            .map(|chunk| (chunk[0], chunk[1], chunk[2]))
+            .collect()
+    }
+}
+
+/// Each instruction's opcode, mnemonic, number of parameters it reads, and
+/// whether it has a parameter it writes to after those
+#[cfg_attr(not(test), allow(dead_code))]
+const INSTRUCTIONS: [(i64, &str, usize, bool); 10] = [
+    (1, "ADD", 2, true),
+    (2, "MUL", 2, true),
+    (3, "IN", 0, true),
+    (4, "OUT", 1, false),
+    (5, "JNZ", 2, false),
+    (6, "JZ", 2, false),
+    (7, "LT", 2, true),
+    (8, "EQ", 2, true),
+    (9, "ARB", 1, false),
+    (99, "HALT", 0, false),
+];
+
+/// Check a program for problems that show up without running it: the first
+/// instruction has to be known, and reading straight through from there up to
+/// the first halt, writes can't be in immediate mode and position-mode
+/// parameters can't be negative. Jumps aren't followed, so this only catches
+/// the obvious mistakes.
+#[cfg_attr(not(test), allow(dead_code))]
+fn validate(program: &[i64]) -> Result<(), IntcodeError> {
+    let mut ip = 0;
+    loop {
+        let (opcode, modes) = decode(program.get(ip).copied().unwrap_or(0));
+        let &(_, _, reads, writes) = match INSTRUCTIONS.iter().find(|&&(code, ..)| code == opcode) {
+            Some(instruction) => instruction,
+            // Past the first instruction this might just be data
+            None if ip > 0 => return Ok(()),
+            None if opcode == 0 => return Err(IntcodeError::HaltedUnexpectedly { ip }),
+            None => return Err(IntcodeError::UnknownOpcode { ip, opcode }),
+        };
+        if opcode == 99 {
+            return Ok(());
+        }
+
+        let count = reads + writes as usize;
+        for (n, &mode) in modes.iter().enumerate().take(count) {
+            let value = program.get(ip + 1 + n).copied().unwrap_or(0);
+            if n == reads && mode == 1 {
+                return Err(IntcodeError::InvalidWriteMode { ip, param: n + 1 });
+            }
+            if mode == 0 && value < 0 {
+                return Err(IntcodeError::InvalidAddress(value));
+            }
+        }
+        ip += 1 + count;
+    }
+}
+
+/// Format a parameter: `#n` is immediate, `[n]` is position, and `[rb+n]` is
+/// relative to the relative base
+#[cfg_attr(not(test), allow(dead_code))]
+fn format_param(mode: u8, value: i64) -> String {
+    match mode {
+        1 => format!("#{}", value),
+        2 => format!("[rb{:+}]", value),
+        _ => format!("[{}]", value),
+    }
+}
+
+/// Parse a parameter formatted by format_param into its (mode, value)
+#[cfg_attr(not(test), allow(dead_code))]
+fn parse_param(token: &str) -> Option<(u8, i64)> {
+    if let Some(value) = token.strip_prefix('#') {
+        return value.parse().ok().map(|value| (1, value));
+    }
+    let inner = token.strip_prefix('[')?.strip_suffix(']')?;
+    match inner.strip_prefix("rb") {
+        Some(offset) if offset.starts_with(['+', '-']) => {
+            offset.parse().ok().map(|value| (2, value))
+        }
+        Some(_) => None,
+        None => inner.parse().ok().map(|value| (0, value)),
+    }
+}
+
+/// Decode the instruction at the address, returning its name, how many
+/// parameters it reads, and how many parameters it has in all, along with the
+/// modes; returns None if the value there isn't a valid instruction or its
+/// parameters run off the end of the program
+#[cfg_attr(not(test), allow(dead_code))]
+fn instruction_at(program: &[i64], ip: usize) -> Option<(&'static str, usize, usize, [u8; 3])> {
+    let (opcode, modes) = decode(*program.get(ip)?);
+    INSTRUCTIONS
+        .iter()
+        .find(|&&(code, ..)| code == opcode)
+        .and_then(|&(_, name, reads, writes)| {
+            let count = reads + writes as usize;
+            if ip + count >= program.len() {
+                return None;
+            }
+            // Only take it if assembling it again gives the same value
+            let mut encoded = opcode;
+            let mut place = 100;
+            for &mode in modes.iter().take(count) {
+                encoded += i64::from(mode) * place;
+                place *= 10;
+            }
+            let bad_mode = modes.iter().take(count).any(|&mode| mode > 2);
+            let bad_write = writes && modes[reads] == 1;
+            if encoded != program[ip] || bad_mode || bad_write {
+                return None;
+            }
+            Some((name, reads, count, modes))
+        })
+}
+
+/// Turn a program into assembly, one instruction per line like
+/// `ADD #1 [4] -> [0]`; values that aren't a valid instruction come out as
+/// `DATA n`
+#[cfg_attr(not(test), allow(dead_code))]
+fn disassemble(program: &[i64]) -> String {
+    let mut lines = Vec::new();
+    let mut ip = 0;
+    while ip < program.len() {
+        match instruction_at(program, ip) {
+            Some((name, reads, count, modes)) => {
+                let mut line = name.to_string();
+                for n in 0..count {
+                    if n == reads {
+                        line.push_str(" ->");
+                    }
+                    line.push(' ');
+                    line.push_str(&format_param(modes[n], program[ip + 1 + n]));
+                }
+                lines.push(line);
+                ip += 1 + count;
+            }
+            None => {
+                lines.push(format!("DATA {}", program[ip]));
+                ip += 1;
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+/// Return the immediate-mode parameter values in the program, in order,
+/// reading it straight through the way disassemble does; values that aren't
+/// valid instructions are skipped
+#[cfg_attr(not(test), allow(dead_code))]
+fn referenced_constants(program: &[i64]) -> Vec<i64> {
+    let mut constants = Vec::new();
+    let mut ip = 0;
+    while ip < program.len() {
+        match instruction_at(program, ip) {
+            Some((_, _, count, modes)) => {
+                for n in 0..count {
+                    if modes[n] == 1 {
+                        constants.push(program[ip + 1 + n]);
+                    }
+                }
+                ip += 1 + count;
+            }
+            None => ip += 1,
+        }
+    }
+    constants
+}
+
+/// Turn assembly in the format disassemble writes back into a program; blank
+/// lines are skipped
+#[cfg_attr(not(test), allow(dead_code))]
+fn assemble(source: &str) -> Result<Vec<i64>, IntcodeError> {
+    let mut program = Vec::new();
+    for line in source
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+    {
+        let invalid = || IntcodeError::InvalidAssembly(line.to_string());
+        let mut tokens = line.split_whitespace();
+        let name = tokens.next().ok_or_else(invalid)?;
+        let tokens: Vec<&str> = tokens.collect();
+
+        if name == "DATA" {
+            match tokens.as_slice() {
+                [value] => program.push(value.parse().map_err(|_| invalid())?),
+                _ => return Err(invalid()),
+            }
+            continue;
+        }
+
+        let &(opcode, _, reads, writes) = INSTRUCTIONS
+            .iter()
+            .find(|&&(_, mnemonic, ..)| mnemonic == name)
+            .ok_or_else(invalid)?;
+        let params: Vec<&str> = match tokens.iter().position(|&token| token == "->") {
+            Some(arrow) if writes && arrow == reads && tokens.len() == reads + 2 => tokens
+                .iter()
+                .filter(|&&token| token != "->")
+                .copied()
+                .collect(),
+            None if !writes && tokens.len() == reads => tokens,
+            _ => return Err(invalid()),
+        };
+
+        let mut instr = opcode;
+        let mut place = 100;
+        let mut values = Vec::new();
+        for (n, token) in params.iter().enumerate() {
+            let (mode, value) = parse_param(token).ok_or_else(invalid)?;
+            if n == reads && mode == 1 {
+                return Err(invalid());
+            }
+            instr += i64::from(mode) * place;
+            place *= 10;
+            values.push(value);
+        }
+        program.push(instr);
+        program.extend(values);
+    }
+    Ok(program)
+}
+
+/// Day 2 solution; the input is the gravity assist program. Input that
+/// doesn't parse gets the parse error as its answer.
+#[cfg_attr(not(test), allow(dead_code))]
+struct Day2;
+
+impl common::Solution for Day2 {
+    fn part1(&self, input: &str) -> String {
+        match parse_usize_program(input) {
+            Ok(program) => run_with_noun_verb(&program, 12, 2).to_string(),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    fn part2(&self, input: &str) -> String {
+        let program = match parse_usize_program(input) {
+            Ok(program) => program,
+            Err(e) => return e.to_string(),
+        };
+        match find_noun_verb(&program, 19690720) {
+            Some((noun, verb)) => (100 * noun + verb).to_string(),
+            None => "none".to_string(),
+        }
+    }
+}
+
+/// Run a chain of amplifiers, one per phase setting, where each one's output
+/// is the next one's input and the first gets a signal of 0. With `feedback`,
+/// the last amplifier's output goes back to the first until they all halt.
+/// Returns the last signal out of the chain.
+///
+/// Panics if an amplifier's program fails.
+#[cfg_attr(not(test), allow(dead_code))]
+fn run_amplifiers(program: &[i64], phases: &[i64], feedback: bool) -> i64 {
+    let mut amps: Vec<Computer> = phases
+        .iter()
+        .map(|&phase| Computer::with_input(program, &[phase]))
+        .collect();
+
+    let mut signal = 0;
+    loop {
+        for amp in amps.iter_mut() {
+            amp.push_input(signal);
+            amp.run_until_input().expect("amplifier program failed");
+            if let Some(&value) = amp.output.last() {
+                signal = value;
+            }
+            amp.output.clear();
+        }
+        if !feedback || amps.iter().all(|amp| amp.halted) {
+            return signal;
+        }
+    }
+}
+
+/// Return every ordering of the values, using Heap's algorithm
+#[cfg_attr(not(test), allow(dead_code))]
+fn permutations(values: &[i64]) -> Vec<Vec<i64>> {
+    let mut values = values.to_vec();
+    let mut result = vec![values.clone()];
+    let mut counters = vec![0; values.len()];
+
+    let mut i = 1;
+    while i < values.len() {
+        if counters[i] < i {
+            if i % 2 == 0 {
+                values.swap(0, i);
+            } else {
+                values.swap(counters[i], i);
+            }
+            result.push(values.clone());
+            counters[i] += 1;
+            i = 1;
+        } else {
+            counters[i] = 0;
+            i += 1;
+        }
+    }
+
+    result
+}
+
+/// Try every ordering of the phase settings in the range and return the one
+/// producing the highest signal along with that signal
+#[cfg_attr(not(test), allow(dead_code))]
+fn best_phase_setting(
+    program: &[i64],
+    phase_range: std::ops::RangeInclusive<i64>,
+    feedback: bool,
+) -> (Vec<i64>, i64) {
+    let phases: Vec<i64> = phase_range.collect();
+    permutations(&phases)
+        .into_iter()
+        .map(|phases| {
+            let signal = run_amplifiers(program, &phases, feedback);
+            (phases, signal)
+        })
+        .max_by_key(|&(_, signal)| signal)
+        .unwrap_or((vec![], 0))
+}
+
+/// Run a program with the given input to halt, returning the final memory and
+/// the output
+#[cfg_attr(not(test), allow(dead_code))]
+fn execute(program: &[i64], input: &[i64]) -> Result<(Vec<i64>, Vec<i64>), IntcodeError> {
+    let mut computer = Computer::with_input(program, input);
+    computer.run()?;
+    Ok((computer.memory, computer.output))
+}
+
+/// Run a program with the given input to halt and return how many values it
+/// output
+#[cfg_attr(not(test), allow(dead_code))]
+fn count_outputs(program: &[i64], input: &[i64]) -> Result<usize, IntcodeError> {
+    let (_, output) = execute(program, input)?;
+    Ok(output.len())
+}
+
+/// Run a copy of the program with each (addr, value) patch applied and return
+/// the final memory; this is run_with_noun_verb for any set of addresses
+#[cfg_attr(not(test), allow(dead_code))]
+fn run_with_patches(program: &[i64], patches: &[(usize, i64)]) -> Result<Vec<i64>, IntcodeError> {
+    let mut computer = Computer::new(program);
+    for &(addr, value) in patches {
+        computer.write(addr as i64, value)?;
+    }
+    computer.run()?;
+    Ok(computer.memory)
+}
+
+/// Run both programs to halt and return each address where their final
+/// memory differs with the value from each run; memory past the end of the
+/// shorter one counts as 0
+#[cfg_attr(not(test), allow(dead_code))]
+fn diff_runs(a: &[i64], b: &[i64]) -> Result<Vec<(usize, i64, i64)>, IntcodeError> {
+    let (memory_a, _) = execute(a, &[])?;
+    let (memory_b, _) = execute(b, &[])?;
+    let len = memory_a.len().max(memory_b.len());
+    Ok((0..len)
+        .map(|addr| {
+            let value_a = memory_a.get(addr).copied().unwrap_or(0);
+            let value_b = memory_b.get(addr).copied().unwrap_or(0);
+            (addr, value_a, value_b)
+        })
+        .filter(|&(_, value_a, value_b)| value_a != value_b)
+        .collect())
+}
+
+/// Return whether the program halts within the given number of steps; a
+/// program that errors or waits for input doesn't count as halting
+#[cfg_attr(not(test), allow(dead_code))]
+fn halts_within(program: &[i64], max_steps: u64) -> bool {
+    let mut computer = Computer::new(program);
+    for _ in 0..max_steps {
+        if computer.halted {
+            break;
+        }
+        if computer.step().is_err() {
+            return false;
+        }
+    }
+    computer.halted
+}
+
+/// Run a diagnostic program with the system ID as its only input and return
+/// the diagnostic code it prints last; every value before it is a self-test
+/// result that has to be 0
+#[cfg_attr(not(test), allow(dead_code))]
+fn diagnostic(program: &[i64], system_id: i64) -> Result<i64, IntcodeError> {
+    let (_, output) = execute(program, &[system_id])?;
+    let (&code, tests) = output.split_last().ok_or(IntcodeError::NoOutput)?;
+    if let Some(&failed) = tests.iter().find(|&&value| value != 0) {
+        return Err(IntcodeError::SelfTestFailed(failed));
+    }
+    Ok(code)
+}
 
-    let mut file = match File::open(Path::new(&datafile_arg)) {
+/// Format output values one per line
+fn format_output(output: &[i64]) -> String {
+    output
+        .iter()
+        .map(|val| val.to_string())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Parse and run a program with the given input, returning its output
+#[cfg_attr(not(test), allow(dead_code))]
+fn run_str(program: &str, input: &[i64]) -> Result<Vec<i64>, IntcodeError> {
+    let (_, output) = execute(&parse_program(program)?, input)?;
+    Ok(output)
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let json = common::wants_json(&args);
+    let verbose = common::has_flag(&args, "--verbose");
+    let part = match common::selected_part(&args) {
+        Ok(part) => part,
         Err(e) => {
-            eprintln!("Can't open file: {}", e);
-            std::process::exit(exitcode::DATAERR);
+            eprintln!("Error: {}.", e);
+            std::process::exit(common::exit_code_for(&e));
         }
-        Ok(file) => file,
     };
 
-    let mut contents = String::new();
-    match file.read_to_string(&mut contents) {
-        Ok(_) => (),
+    // First arg is the data file path; read stdin if there isn't one
+    let contents = match common::read_input(common::first_positional(&args)) {
         Err(e) => {
-            eprintln!("Can't read file: {}", e);
-            std::process::exit(exitcode::DATAERR);
+            eprintln!("Can't read input: {}", e);
+            std::process::exit(common::exit_code_for(&e.into()));
         }
+        Ok(contents) => contents,
     };
 
-    // Remove whitespace from beginning and end
-    let contents = contents.trim();
-    println!("input: {}", contents);
+    if !json {
+        println!("input: {}", contents.trim());
+    }
 
-    // Split the input on "," and convert to usize
-    let mut prog_input: Vec<usize> = contents
-        .split(',')
-        .map(|val| usize::from_str_radix(val, 10).unwrap())
-        .collect();
+    // With --input=N, programs that print output (like day 5's diagnostics)
+    // are run with those values and their output is printed; --verbose also
+    // shows how much memory the run used. The run is cut off after
+    // OUTPUT_RUN_TIMEOUT so a program that never halts can't hang.
+    if !json && !common::flag_values(&args, "--input").is_empty() {
+        let inputs = common::flag_values(&args, "--input")
+            .iter()
+            .map(|val| {
+                val.parse::<i64>().map_err(|_| {
+                    common::AocError::Usage(format!("--input must be a number, not {:?}", val))
+                })
+            })
+            .collect::<Result<Vec<i64>, _>>();
+        let inputs = match inputs {
+            Ok(inputs) => inputs,
+            Err(e) => {
+                eprintln!("Error: {}.", e);
+                std::process::exit(common::exit_code_for(&e));
+            }
+        };
+        let run = parse_program(&contents).and_then(|program| {
+            let mut computer = Computer::with_input(&program, &inputs);
+            let state = computer.run_with_timeout(OUTPUT_RUN_TIMEOUT)?;
+            Ok((computer, state))
+        });
+        match run {
+            Ok((_, RunState::TimedOut)) => {
+                let e = common::AocError::Data(format!(
+                    "the program didn't halt within {} seconds",
+                    OUTPUT_RUN_TIMEOUT.as_secs()
+                ));
+                eprintln!("Error: {}.", e);
+                std::process::exit(common::exit_code_for(&e));
+            }
+            Ok((computer, _)) => {
+                if verbose {
+                    println!("peak memory: {} cells", computer.memory_size());
+                }
+                if !computer.output.is_empty() {
+                    println!("{}", format_output(&computer.output));
+                    return;
+                }
+            }
+            Err(e) => {
+                let e = common::AocError::from(e);
+                eprintln!("Error: {}.", e);
+                std::process::exit(common::exit_code_for(&e));
+            }
+        }
+    }
+
+    let prog_input = match parse_usize_program(&contents) {
+        Ok(prog_input) => prog_input,
+        Err(e) => {
+            eprintln!("Error: {}.", e);
+            std::process::exit(common::exit_code_for(&e));
+        }
+    };
+
+    // Part 1 is the program's output with noun 12 and verb 2
+    let part1 = part.part1().then(|| {
+        let part1 = run_with_noun_verb(&prog_input, 12, 2);
+        if !json {
+            println!("1202 position 0: {}", part1);
+        }
+        part1
+    });
+
+    if !part.part2() {
+        if json {
+            println!(
+                "{}",
+                common::format_json_parts(part1.map(|v| v as i64), None)
+            );
+        }
+        return;
+    }
+
+    let part2 = find_noun_verb(&prog_input, 19690720).map(|(noun, verb)| {
+        if !json {
+            println!("eureka!: noun={} verb={}", noun, verb);
+            println!("{}", 100 * noun + verb);
+        }
+        100 * noun + verb
+    });
+
+    if json {
+        match part2 {
+            Some(part2) => println!(
+                "{}",
+                common::format_json_parts(part1.map(|v| v as i64), Some(part2 as i64))
+            ),
+            None => {
+                let e = common::AocError::Data("no noun and verb produce 19690720".to_string());
+                eprintln!("Error: {}.", e);
+                std::process::exit(common::exit_code_for(&e));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test decoding opcode and modes
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode(1002), (2, [0, 1, 0]));
+        assert_eq!(decode(11101), (1, [1, 1, 1]));
+        assert_eq!(decode(2), (2, [0, 0, 0]));
+    }
+
+    /// Test parsing program text like it comes from a file or stdin
+    #[test]
+    fn test_parse_usize_program() {
+        let contents = common::read_from(std::io::Cursor::new("1,0,0,0,99\n")).unwrap();
+        assert_eq!(parse_usize_program(&contents), Ok(vec![1, 0, 0, 0, 99]));
+        assert_eq!(
+            parse_usize_program("1,0,-1,0,99"),
+            Err(common::AocError::Parse {
+                token: "-1".to_string(),
+                index: 2
+            })
+        );
+    }
+
+    /// Test 1 oper
+    #[test]
+    fn test_run_program_oper_1() {
+        assert_eq!(run_program(&[1, 0, 0, 0, 99]), vec![2, 0, 0, 0, 99]);
+    }
+
+    /// Test 2 oper
+    #[test]
+    fn test_run_program_oper_2() {
+        assert_eq!(run_program(&[2, 3, 0, 3, 99]), vec![2, 3, 0, 6, 99]);
+    }
+
+    /// Test complex
+    #[test]
+    fn test_run_program_complex() {
+        assert_eq!(
+            run_program(&[1, 1, 1, 4, 99, 5, 6, 0, 99]),
+            vec![30, 1, 1, 4, 2, 5, 6, 0, 99]
+        );
+    }
+
+    /// Test patching noun and verb
+    #[test]
+    fn test_run_with_noun_verb() {
+        let program = vec![1, 0, 0, 3, 2, 3, 11, 0, 99, 30, 40, 50];
+        assert_eq!(run_with_noun_verb(&program, 9, 10), 3500);
+        // The program itself isn't changed
+        assert_eq!(program[1], 0);
+    }
+
+    /// Program whose position 0 output is memory[noun] * memory[verb], with
+    /// memory[k] == k past the first instruction
+    fn multiply_program() -> Vec<usize> {
+        let mut program = vec![2, 0, 0, 0, 99];
+        program.extend(5..100);
+        program
+    }
+
+    /// Test finding the noun and verb for a target
+    #[test]
+    fn test_find_noun_verb() {
+        let program = multiply_program();
+        assert_eq!(find_noun_verb(&program, 1), Some((1, 1)));
+        assert_eq!(find_noun_verb(&program, 30), Some((0, 15)));
+        assert_eq!(find_noun_verb(&program, 10007), None);
+    }
+
+    /// Test finding every noun and verb for a target
+    #[test]
+    fn test_all_noun_verb() {
+        let program = multiply_program();
+        let pairs = all_noun_verb(&program, 30);
+        assert!(pairs.contains(&(5, 6)));
+        assert!(pairs.contains(&(6, 5)));
+        assert_eq!(pairs.first().copied(), find_noun_verb(&program, 30));
+        assert!(pairs
+            .iter()
+            .all(|&(noun, verb)| run_with_noun_verb(&program, noun, verb) == 30));
+
+        assert_eq!(all_noun_verb(&program, 10007), vec![]);
+    }
+
+    /// Test that the search tries noun and verb 99
+    #[test]
+    fn test_find_noun_verb_inclusive() {
+        // Position 0 is memory[noun] + memory[verb] and only memory[99] is big
+        let mut program = vec![1, 0, 0, 0, 99];
+        program.resize(99, 0);
+        program.push(1000);
+
+        assert_eq!(find_noun_verb(&program, 2000), Some((99, 99)));
+
+        // The old exclusive range never finds it
+        for noun in 0..99 {
+            for verb in 0..99 {
+                assert_ne!(run_with_noun_verb(&program, noun, verb), 2000);
+            }
+        }
+    }
+
+    /// Test the Solution impl
+    #[test]
+    fn test_solution() {
+        use common::Solution;
+
+        let input = multiply_program()
+            .iter()
+            .map(|val| val.to_string())
+            .collect::<Vec<String>>()
+            .join(",");
+        assert_eq!(Day2.part1(&input), "24");
+        assert_eq!(Day2.part2(&input), "none");
+
+        assert_eq!(Day2.part1("1,0,x"), "can't parse \"x\" (item 2)");
+        assert_eq!(Day2.part2("1,0,x"), "can't parse \"x\" (item 2)");
+    }
+
+    /// Test running a program from a string
+    #[test]
+    fn test_run_str() {
+        assert_eq!(run_str("3,0,4,0,99", &[7]), Ok(vec![7]));
+    }
+
+    /// Test that add and multiply wrap on overflow
+    #[test]
+    fn test_overflow_wraps() {
+        let (memory, _) = execute(&[1101, i64::MAX, 1, 0, 99], &[]).unwrap();
+        assert_eq!(memory[0], i64::MIN);
+
+        let (memory, _) = execute(&[1102, i64::MAX, 2, 0, 99], &[]).unwrap();
+        assert_eq!(memory[0], -2);
+
+        assert_eq!(
+            run_program(&[1, 0, 5, 0, 99, usize::MAX]),
+            vec![0, 0, 5, 0, 99, usize::MAX]
+        );
+    }
+
+    /// Test reporting intcode errors as shared errors
+    #[test]
+    fn test_intcode_error_into_aoc_error() {
+        let err = common::AocError::from(IntcodeError::NoInput);
+        assert_eq!(
+            err,
+            common::AocError::Data("no input available".to_string())
+        );
+    }
+
+    /// Test getting the final memory and output
+    #[test]
+    fn test_execute() {
+        assert_eq!(
+            execute(&[1, 1, 1, 4, 99, 5, 6, 0, 99], &[]),
+            Ok((vec![30, 1, 1, 4, 2, 5, 6, 0, 99], vec![]))
+        );
+        assert_eq!(
+            execute(&[3, 0, 4, 0, 99], &[7]),
+            Ok((vec![7, 0, 4, 0, 99], vec![7]))
+        );
+    }
+
+    /// Test patching several addresses before running
+    #[test]
+    fn test_run_with_patches() {
+        let program = vec![1, 0, 0, 3, 2, 3, 11, 0, 99, 30, 40, 50];
+        let memory = run_with_patches(&program, &[(1, 9), (2, 10)]).unwrap();
+        assert_eq!(memory[0], 3500);
+        assert_eq!(&memory[1..3], &[9, 10]);
+
+        // Turn the multiply into an add: 1 + 1 into position 3, then 2 + 50
+        let memory = run_with_patches(&program, &[(9, 1), (4, 1)]).unwrap();
+        assert_eq!(memory[9], 1);
+        assert_eq!(memory[3], 2);
+        assert_eq!(memory[0], 52);
+
+        assert_eq!(
+            run_with_patches(&program, &[]),
+            execute(&program, &[]).map(|(m, _)| m)
+        );
+        assert_eq!(
+            run_with_patches(&program, &[(MAX_MEMORY, 1)]),
+            Err(IntcodeError::InvalidAddress(MAX_MEMORY as i64))
+        );
+    }
+
+    /// Test comparing the final memory of two runs
+    #[test]
+    fn test_diff_runs() {
+        let program = vec![1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50];
+        assert_eq!(diff_runs(&program, &program), Ok(vec![]));
+
+        // Multiplying by 51 instead of 50 only changes the result
+        let mut changed = program.clone();
+        changed[11] = 51;
+        assert_eq!(
+            diff_runs(&program, &changed),
+            Ok(vec![(0, 3500, 3570), (11, 50, 51)])
+        );
+
+        assert_eq!(
+            diff_runs(&[1101, 1, 1, 5, 99], &[1101, 1, 1, 6, 99]),
+            Ok(vec![(3, 5, 6), (5, 2, 0), (6, 0, 2)])
+        );
+        assert_eq!(diff_runs(&program, &[3, 0, 99]), Err(IntcodeError::NoInput));
+    }
+
+    /// Test checking whether a program halts
+    #[test]
+    fn test_halts_within() {
+        let program = vec![1, 0, 0, 3, 2, 3, 11, 0, 99, 30, 40, 50];
+        assert!(halts_within(&program, 3));
+        assert!(!halts_within(&program, 2));
+
+        // Jump back to itself forever
+        assert!(!halts_within(&[1105, 1, 0], 1000));
+
+        // Waiting for input and bad opcodes aren't halting
+        assert!(!halts_within(&[3, 0, 99], 10));
+        assert!(!halts_within(&[42, 99], 10));
+    }
+
+    /// Test running a program with modes and negative values
+    #[test]
+    fn test_run_str_modes() {
+        assert_eq!(run_str("1002,4,3,4,33", &[]), Ok(vec![]));
+        assert_eq!(run_str("1101,100,-1,4,0", &[]), Ok(vec![]));
+        assert_eq!(run_str("104,-5,99", &[]), Ok(vec![-5]));
+    }
+
+    /// Test errors
+    #[test]
+    fn test_run_str_errors() {
+        assert_eq!(
+            run_str("1,0,x", &[]),
+            Err(IntcodeError::Parse("x".to_string()))
+        );
+        assert_eq!(run_str("3,0,99", &[]), Err(IntcodeError::NoInput));
+        assert_eq!(
+            run_str("42,0,99", &[]),
+            Err(IntcodeError::UnknownOpcode { ip: 0, opcode: 42 })
+        );
+    }
+
+    /// Test that writing to an immediate-mode parameter is an error
+    #[test]
+    fn test_invalid_write_mode() {
+        assert_eq!(
+            run_str("11101,1,1,5,99,0", &[]),
+            Err(IntcodeError::InvalidWriteMode { ip: 0, param: 3 })
+        );
+        assert_eq!(
+            run_str("1101,1,1,9,103,0,99", &[1]),
+            Err(IntcodeError::InvalidWriteMode { ip: 4, param: 1 })
+        );
+        assert_eq!(
+            IntcodeError::InvalidWriteMode { ip: 4, param: 1 }.to_string(),
+            "parameter 1 at 4 is written to in immediate mode"
+        );
+    }
+
+    /// Test counting the values a program outputs
+    #[test]
+    fn test_count_outputs() {
+        // Outputs a countdown from the input value to 1
+        let program = [3, 12, 4, 12, 1001, 12, -1, 12, 1005, 12, 2, 99, 0];
+        assert_eq!(count_outputs(&program, &[5]), Ok(5));
+        assert_eq!(count_outputs(&program, &[1]), Ok(1));
+        assert_eq!(count_outputs(&[1, 0, 0, 0, 99], &[]), Ok(0));
+        assert_eq!(count_outputs(&program, &[]), Err(IntcodeError::NoInput));
+    }
+
+    /// Test getting the diagnostic code
+    #[test]
+    fn test_diagnostic() {
+        assert_eq!(diagnostic(&[104, 0, 104, 0, 104, 42, 99], 1), Ok(42));
+        assert_eq!(diagnostic(&[3, 0, 4, 0, 99], 5), Ok(5));
+
+        assert_eq!(
+            diagnostic(&[104, 0, 104, 3, 104, 42, 99], 1),
+            Err(IntcodeError::SelfTestFailed(3))
+        );
+        assert_eq!(diagnostic(&[99], 1), Err(IntcodeError::NoOutput));
+    }
+
+    /// Test checking programs before running them
+    #[test]
+    fn test_validate() {
+        assert_eq!(
+            validate(&[1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50]),
+            Ok(())
+        );
+        assert_eq!(validate(&[109, -3, 204, -1, 99]), Ok(()));
+        // Whatever comes after the halt is data
+        assert_eq!(validate(&[99, 11101, -5]), Ok(()));
+
+        assert_eq!(
+            validate(&[1, 0, 0, 0, 11101, 1, 1, 5, 99]),
+            Err(IntcodeError::InvalidWriteMode { ip: 4, param: 3 })
+        );
+        assert_eq!(
+            validate(&[103, 0, 99]),
+            Err(IntcodeError::InvalidWriteMode { ip: 0, param: 1 })
+        );
+        assert_eq!(
+            validate(&[1, 0, -4, 0, 99]),
+            Err(IntcodeError::InvalidAddress(-4))
+        );
+        assert_eq!(
+            validate(&[42, 99]),
+            Err(IntcodeError::UnknownOpcode { ip: 0, opcode: 42 })
+        );
+        assert_eq!(
+            validate(&[]),
+            Err(IntcodeError::HaltedUnexpectedly { ip: 0 })
+        );
+    }
+
+    /// Test collecting immediate values
+    #[test]
+    fn test_referenced_constants() {
+        let program = [
+            1101, 5, -7, 0, 3, 0, 1008, 0, 42, 1, 104, 13, 109, 3, 1106, 0, 0, 99,
+        ];
+        assert_eq!(referenced_constants(&program), vec![5, -7, 42, 13, 3, 0, 0]);
+        assert_eq!(referenced_constants(&[1, 0, 0, 0, 99]), vec![]);
+        // The value past the halt isn't an instruction
+        assert_eq!(referenced_constants(&[99, 7]), vec![]);
+    }
+
+    /// Test turning programs into assembly
+    #[test]
+    fn test_disassemble() {
+        assert_eq!(
+            disassemble(&[1002, 4, 3, 4, 33]),
+            "MUL [4] #3 -> [4]\nDATA 33"
+        );
+        assert_eq!(
+            disassemble(&[109, -2, 203, 4, 2204, 99]),
+            "ARB #-2\nIN -> [rb+4]\nDATA 2204\nHALT"
+        );
+        assert_eq!(disassemble(&[1101, 1, 2]), "DATA 1101\nDATA 1\nDATA 2");
+        assert_eq!(disassemble(&[]), "");
+    }
+
+    /// Test assembling source back into a program
+    #[test]
+    fn test_assemble() -> Result<(), IntcodeError> {
+        let source = "IN -> [20]\nADD [20] #-1 -> [rb+21]\nOUT [21]\nJNZ #0 #0\nHALT";
+        let program = assemble(source)?;
+        assert_eq!(
+            program,
+            vec![3, 20, 21001, 20, -1, 21, 4, 21, 1105, 0, 0, 99]
+        );
+        assert_eq!(disassemble(&program), source);
+        assert_eq!(execute(&program, &[5])?.1, vec![4]);
+
+        let program = vec![1002, 4, 3, 4, 33];
+        assert_eq!(assemble(&disassemble(&program))?, program);
+
+        assert_eq!(
+            assemble("ADD #1 #2 -> #0"),
+            Err(IntcodeError::InvalidAssembly("ADD #1 #2 -> #0".to_string()))
+        );
+        assert!(assemble("ADD #1 -> [0]").is_err());
+        assert!(assemble("OUT #1 -> [0]").is_err());
+        assert!(assemble("NOP").is_err());
+        assert!(assemble("OUT [rb]").is_err());
+        Ok(())
+    }
+
+    /// Test that running into a 0 has its own error
+    #[test]
+    fn test_halted_unexpectedly() {
+        assert_eq!(
+            run_str("1101,1,1,5,0", &[]),
+            Err(IntcodeError::HaltedUnexpectedly { ip: 4 })
+        );
+        // Running past the end reads zeroes too
+        assert_eq!(
+            run_str("1101,1,1,0", &[]),
+            Err(IntcodeError::HaltedUnexpectedly { ip: 4 })
+        );
+        assert_eq!(
+            IntcodeError::HaltedUnexpectedly { ip: 4 }.to_string(),
+            "ran into a 0 at 4; is it data?"
+        );
+    }
+
+    /// Test configuring which opcode halts
+    #[test]
+    fn test_config_halt_opcode() {
+        let config = Config {
+            halt_opcode: 42,
+            ..Config::default()
+        };
+        let mut computer = Computer::with_config(&[1101, 2, 3, 0, 42, 5], config);
+        assert_eq!(computer.run(), Ok(()));
+        assert!(computer.halted);
+        assert_eq!(computer.ip, 4);
+        assert_eq!(computer.memory[0], 5);
+    }
+
+    /// Test configuring unknown opcodes to halt
+    #[test]
+    fn test_config_halt_on_unknown() {
+        let config = Config {
+            halt_on_unknown: true,
+            ..Config::default()
+        };
+        let mut computer = Computer::with_config(&[104, 1, 77, 104, 2], config);
+        assert_eq!(computer.run(), Ok(()));
+        assert_eq!(computer.output, vec![1]);
+
+        let mut computer = Computer::new(&[104, 1, 77, 104, 2]);
+        assert_eq!(
+            computer.run(),
+            Err(IntcodeError::UnknownOpcode { ip: 2, opcode: 77 })
+        );
+    }
+
+    /// Test draining output as triples
+    #[test]
+    fn test_drain_triples() {
+        let mut computer = Computer::new(&[104, 1, 104, 2, 104, 3, 104, 6, 104, 5, 104, 4, 99]);
+        computer.run().unwrap();
+        assert_eq!(computer.drain_triples(), vec![(1, 2, 3), (6, 5, 4)]);
+        assert!(computer.output.is_empty());
+
+        let mut computer = Computer::new(&[104, 1, 104, 2, 104, 3, 104, 7, 99]);
+        computer.run().unwrap();
+        assert_eq!(computer.drain_triples(), vec![(1, 2, 3)]);
+        assert_eq!(computer.output, vec![7]);
+    }
+
+    /// Test creating a machine with initial input
+    #[test]
+    fn test_with_input() {
+        let mut computer = Computer::with_input(&[3, 0, 3, 1, 4, 1, 4, 0, 99], &[2, 9]);
+        assert_eq!(computer.input, vec![2, 9]);
+        computer.run().unwrap();
+        assert_eq!(computer.output, vec![9, 2]);
+        assert!(computer.input.is_empty());
+    }
+
+    /// Test pausing for input and resuming
+    #[test]
+    fn test_push_input() {
+        let mut computer = Computer::new(&[3, 0, 4, 0, 3, 0, 4, 0, 99]);
+        assert_eq!(computer.run_until_input(), Ok(RunState::NeedsInput));
+        assert_eq!(computer.ip, 0);
+
+        computer.push_input(5);
+        assert_eq!(computer.run_until_input(), Ok(RunState::NeedsInput));
+        assert_eq!(computer.output, vec![5]);
+
+        computer.push_input(-8);
+        assert_eq!(computer.run_until_input(), Ok(RunState::Halted));
+        assert_eq!(computer.output, vec![5, -8]);
+    }
+
+    /// Test stopping a runaway program after a wall-clock limit
+    #[test]
+    fn test_run_with_timeout() {
+        // Jumps back to itself forever
+        let mut computer = Computer::new(&[1105, 1, 0]);
+        assert_eq!(
+            computer.run_with_timeout(Duration::from_millis(20)),
+            Ok(RunState::TimedOut)
+        );
+        assert!(!computer.halted);
+
+        let mut computer = Computer::new(&[1, 0, 0, 0, 99]);
+        assert_eq!(
+            computer.run_with_timeout(Duration::from_secs(1)),
+            Ok(RunState::Halted)
+        );
+        assert_eq!(computer.memory[0], 2);
+    }
+
+    /// Test the comparison and jump opcodes
+    #[test]
+    fn test_compare_and_jump() {
+        // Is the input equal to 8?
+        assert_eq!(run_str("3,9,8,9,10,9,4,9,99,-1,8", &[8]), Ok(vec![1]));
+        assert_eq!(run_str("3,3,1108,-1,8,3,4,3,99", &[7]), Ok(vec![0]));
+        // Is the input less than 8?
+        assert_eq!(run_str("3,9,7,9,10,9,4,9,99,-1,8", &[7]), Ok(vec![1]));
+        assert_eq!(run_str("3,3,1107,-1,8,3,4,3,99", &[8]), Ok(vec![0]));
+        // Is the input non-zero?
+        let program = "3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9";
+        assert_eq!(run_str(program, &[0]), Ok(vec![0]));
+        assert_eq!(run_str(program, &[5]), Ok(vec![1]));
+        let program = "3,3,1105,-1,9,1101,0,0,12,4,12,99,1";
+        assert_eq!(run_str(program, &[0]), Ok(vec![0]));
+        assert_eq!(run_str(program, &[5]), Ok(vec![1]));
+    }
+
+    /// Test running amplifier chains
+    #[test]
+    fn test_run_amplifiers() {
+        let program = parse_program("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0").unwrap();
+        assert_eq!(run_amplifiers(&program, &[4, 3, 2, 1, 0], false), 43210);
+
+        let program = parse_program(
+            "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,\
+             0,0,5",
+        )
+        .unwrap();
+        assert_eq!(run_amplifiers(&program, &[9, 8, 7, 6, 5], true), 139629729);
+    }
+
+    /// Test generating permutations
+    #[test]
+    fn test_permutations() {
+        let mut perms = permutations(&[1, 2, 3]);
+        perms.sort();
+        assert_eq!(
+            perms,
+            vec![
+                vec![1, 2, 3],
+                vec![1, 3, 2],
+                vec![2, 1, 3],
+                vec![2, 3, 1],
+                vec![3, 1, 2],
+                vec![3, 2, 1]
+            ]
+        );
+        assert_eq!(permutations(&[0, 1, 2, 3, 4]).len(), 120);
+        assert_eq!(permutations(&[]), vec![vec![]]);
+    }
+
+    /// Test finding the best phase setting
+    #[test]
+    fn test_best_phase_setting() {
+        let program = parse_program("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0").unwrap();
+        assert_eq!(
+            best_phase_setting(&program, 0..=4, false),
+            (vec![4, 3, 2, 1, 0], 43210)
+        );
+
+        let program = parse_program(
+            "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,\
+             0,0,5",
+        )
+        .unwrap();
+        assert_eq!(
+            best_phase_setting(&program, 5..=9, true),
+            (vec![9, 8, 7, 6, 5], 139629729)
+        );
+    }
+
+    /// Test that memory grows to fit writes
+    #[test]
+    fn test_memory_size() {
+        let mut computer = Computer::new(&[1101, 2, 3, 1000, 4, 1000, 99]);
+        assert_eq!(computer.memory_size(), 7);
+        computer.run().unwrap();
+        assert_eq!(computer.memory_size(), 1001);
+        assert_eq!(computer.output, vec![5]);
+        assert_eq!(computer.memory[999], 0);
+
+        // Reading past the end doesn't grow memory
+        let mut computer = Computer::new(&[4, 500, 99]);
+        computer.run().unwrap();
+        assert_eq!(computer.output, vec![0]);
+        assert_eq!(computer.memory_size(), 3);
+
+        let mut computer = Computer::new(&[1101, 2, 3, MAX_MEMORY as i64, 99]);
+        assert_eq!(
+            computer.run(),
+            Err(IntcodeError::InvalidAddress(MAX_MEMORY as i64))
+        );
+        let mut computer = Computer::new(&[1101, 2, 3, -1, 99]);
+        assert_eq!(computer.run(), Err(IntcodeError::InvalidAddress(-1)));
+    }
+
+    /// Test formatting output one value per line
+    #[test]
+    fn test_format_output() {
+        assert_eq!(format_output(&[0, 0, -3, 15386262]), "0\n0\n-3\n15386262");
+        assert_eq!(format_output(&[7]), "7");
+        assert_eq!(format_output(&[]), "");
+    }
+
+    /// Test starting from a different address
+    #[test]
+    fn test_set_ip() {
+        let mut computer = Computer::new(&[99, 0, 0, 0]);
+        // Poke in a routine at 4 that adds 2 and 3 into 0
+        for (offset, &val) in [1101, 2, 3, 0, 104, 7, 99].iter().enumerate() {
+            computer.write(4 + offset as i64, val).unwrap();
+        }
+        computer.set_ip(4);
+        computer.run().unwrap();
+        assert_eq!(computer.memory[0], 5);
+        assert_eq!(computer.output, vec![7]);
+        assert_eq!(computer.ip, 10);
+    }
+
+    /// Test relative mode and adjusting the relative base
+    #[test]
+    fn test_relative_base() {
+        // Quine from day 9
+        let program = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+        assert_eq!(run_str(program, &[]), Ok(parse_program(program).unwrap()));
+
+        // Read into and write from a relative address
+        assert_eq!(run_str("109,10,203,-3,204,-3,99", &[42]), Ok(vec![42]));
+        assert_eq!(
+            run_str("104,1125899906842624,99", &[]),
+            Ok(vec![1125899906842624])
+        );
+    }
+
+    /// Test resetting a machine
+    #[test]
+    fn test_reset() {
+        let config = Config {
+            count_opcodes: true,
+            ..Config::default()
+        };
+        let mut computer =
+            Computer::with_config(&[109, 3, 3, 20, 204, 17, 99], config).with_write_log(true);
+        computer.push_input(11);
+        computer.run().unwrap();
+        assert_eq!(computer.output, vec![11]);
+        assert!(computer.halted);
+        assert!(!computer.opcode_histogram().is_empty());
+        assert!(!computer.write_log().is_empty());
+
+        computer.reset();
+        assert_eq!(computer.memory, vec![109, 3, 3, 20, 204, 17, 99]);
+        assert_eq!(computer.ip, 0);
+        assert_eq!(computer.relative_base, 0);
+        assert!(!computer.halted);
+        assert!(computer.output.is_empty());
+        assert!(computer.opcode_histogram().is_empty());
+        assert!(computer.write_log().is_empty());
+
+        computer.push_input(11);
+        computer.run().unwrap();
+        assert_eq!(computer.output, vec![11]);
+    }
+
+    /// Test pulling output values one at a time
+    #[test]
+    fn test_run_until_output() {
+        let mut computer = Computer::with_input(&[104, 1, 3, 9, 4, 9, 104, 3, 99, 0], &[2]);
+        assert_eq!(computer.run_until_output(), Ok(Some(1)));
+        assert_eq!(computer.run_until_output(), Ok(Some(2)));
+        assert_eq!(computer.run_until_output(), Ok(Some(3)));
+        assert!(computer.output.is_empty());
+        assert_eq!(computer.run_until_output(), Ok(None));
+        assert!(computer.halted);
+        assert_eq!(computer.run_until_output(), Ok(None));
+
+        let mut computer = Computer::new(&[3, 0, 99]);
+        assert_eq!(computer.run_until_output(), Err(IntcodeError::NoInput));
+    }
+
+    /// Test reading the output as text
+    #[test]
+    fn test_output_as_string() {
+        let mut computer = Computer::new(&[99]);
+        computer.output = "Hello\n".bytes().map(i64::from).collect();
+        assert_eq!(computer.output_as_string(), "Hello\n");
+
+        // A big value at the end is the answer, not text
+        computer.output.extend([12345, 65]);
+        assert_eq!(computer.output_as_string(), "Hello\n");
+
+        let (_, output) = execute(&[104, 104, 104, 105, 99], &[]).unwrap();
+        computer.output = output;
+        assert_eq!(computer.output_as_string(), "hi");
+
+        computer.output = vec![255];
+        assert_eq!(computer.output_as_string(), "\u{fffd}");
+    }
+
+    /// Test the input and output counts
+    #[test]
+    fn test_io_len() {
+        let mut computer = Computer::new(&[3, 0, 4, 0, 3, 0, 4, 0, 99]);
+        assert_eq!((computer.input_len(), computer.output_len()), (0, 0));
+        assert_eq!(computer.run_until_input(), Ok(RunState::NeedsInput));
+
+        computer.push_input(1);
+        computer.push_input(2);
+        assert_eq!(computer.input_len(), 2);
+
+        computer.step().unwrap();
+        computer.step().unwrap();
+        assert_eq!((computer.input_len(), computer.output_len()), (1, 1));
+
+        computer.run().unwrap();
+        assert_eq!((computer.input_len(), computer.output_len()), (0, 2));
+    }
+
+    /// Test saving and loading a machine partway through a run
+    #[test]
+    fn test_save_state() {
+        let program = vec![109, 5, 3, 20, 204, 15, 3, 21, 4, 21, 99];
+        let mut computer = Computer::with_input(&program, &[7, 8]);
+        computer.step().unwrap();
+        computer.step().unwrap();
+        computer.step().unwrap();
+
+        let bytes = computer.save_state();
+        let mut loaded = Computer::load_state(&bytes).unwrap();
+        assert_eq!(loaded.memory, computer.memory);
+        assert_eq!(loaded.ip, computer.ip);
+        assert_eq!(loaded.relative_base, 5);
+        assert_eq!(loaded.input, computer.input);
+        assert_eq!(loaded.output, vec![7]);
+        assert_eq!(loaded.save_state(), bytes);
+
+        // Both machines carry on the same way
+        computer.run().unwrap();
+        loaded.run().unwrap();
+        assert_eq!(loaded.output, computer.output);
+        assert_eq!(loaded.memory, computer.memory);
+        assert!(loaded.halted);
+
+        loaded.reset();
+        assert_eq!(loaded.memory, program);
+
+        let halted = Computer::load_state(&computer.save_state()).unwrap();
+        assert!(halted.halted);
+    }
+
+    /// Test loading bad state
+    #[test]
+    fn test_load_state_invalid() {
+        let bytes = Computer::with_input(&[3, 0, 99], &[1]).save_state();
+        assert!(Computer::load_state(&bytes).is_ok());
+        assert_eq!(
+            Computer::load_state(&bytes[..bytes.len() - 1]).err(),
+            Some(IntcodeError::InvalidState)
+        );
+
+        let mut extra = bytes.clone();
+        extra.extend(0_i64.to_le_bytes());
+        assert!(Computer::load_state(&extra).is_err());
+
+        let mut bad_flag = bytes.clone();
+        bad_flag[0] = 2;
+        assert!(Computer::load_state(&bad_flag).is_err());
+
+        assert!(Computer::load_state(&[]).is_err());
+    }
+
+    /// Test loading another program into the same machine
+    #[test]
+    fn test_load() {
+        let mut computer = Computer::with_input(&[3, 0, 4, 0, 99], &[7]);
+        computer.run().unwrap();
+        assert_eq!(computer.output, vec![7]);
+
+        computer.load(&[1101, 2, 3, 5, 99, 0]);
+        assert_eq!(computer.ip, 0);
+        assert!(!computer.halted);
+        assert!(computer.output.is_empty());
+        computer.run().unwrap();
+        assert_eq!(computer.memory, vec![1101, 2, 3, 5, 99, 5]);
+
+        // Reset goes back to the loaded program, not the first one
+        computer.reset();
+        assert_eq!(computer.memory, vec![1101, 2, 3, 5, 99, 0]);
+
+        computer.load(&[104, -1, 99]);
+        computer.run().unwrap();
+        assert_eq!(computer.output, vec![-1]);
+        assert_eq!(computer.memory, vec![104, -1, 99]);
+    }
+
+    /// Test hashing the final memory
+    #[test]
+    fn test_memory_fingerprint() {
+        let program = [1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50];
+        let mut first = Computer::new(&program);
+        first.run().unwrap();
+        let mut second = Computer::new(&program);
+        second.run().unwrap();
+        assert_eq!(first.memory_fingerprint(), second.memory_fingerprint());
+
+        let mut patched = program;
+        patched[11] = 51;
+        let mut third = Computer::new(&patched);
+        third.run().unwrap();
+        assert_ne!(first.memory_fingerprint(), third.memory_fingerprint());
+
+        // The FNV-1a offset basis for no bytes at all
+        assert_eq!(
+            Computer::new(&[]).memory_fingerprint(),
+            0xcbf2_9ce4_8422_2325
+        );
+    }
+
+    /// Test tracking the highest instruction pointer
+    #[test]
+    fn test_max_ip() {
+        // Jumps over the data at 3 and 4 to the halt at 9
+        let mut computer = Computer::new(&[1105, 1, 9, 4, 4, 99, 0, 0, 0, 99]);
+        computer.run().unwrap();
+        assert_eq!(computer.max_ip(), 9);
+
+        // Jumps forward, then back to the halt at the start of the program
+        let mut computer = Computer::new(&[1106, 0, 6, 99, 0, 0, 1105, 1, 3]);
+        computer.run().unwrap();
+        assert_eq!(computer.max_ip(), 6);
+        assert_eq!(computer.ip, 3);
+
+        computer.reset();
+        assert_eq!(computer.max_ip(), 0);
+    }
+
+    /// Test describing each instruction as it runs
+    #[test]
+    fn test_step_described() {
+        let mut computer = Computer::with_input(&[3, 9, 1001, 9, 5, 9, 4, 9, 99, 0], &[2]);
+        assert_eq!(
+            computer.step_described(),
+            Ok(StepInfo {
+                ip: 0,
+                name: "IN",
+                params: vec![(9, 0)],
+                write: Some((9, 2)),
+            })
+        );
+        assert_eq!(
+            computer.step_described(),
+            Ok(StepInfo {
+                ip: 2,
+                name: "ADD",
+                params: vec![(9, 0), (5, 1), (9, 0)],
+                write: Some((9, 7)),
+            })
+        );
+        assert_eq!(
+            computer.step_described(),
+            Ok(StepInfo {
+                ip: 6,
+                name: "OUT",
+                params: vec![(9, 0)],
+                write: None,
+            })
+        );
+        let info = computer.step_described().unwrap();
+        assert_eq!((info.ip, info.name), (8, "HALT"));
+        assert!(computer.halted);
+        assert_eq!(computer.output, vec![7]);
+
+        let mut computer = Computer::new(&[42]);
+        assert_eq!(
+            computer.step_described(),
+            Err(IntcodeError::UnknownOpcode { ip: 0, opcode: 42 })
+        );
+    }
+
+    /// Test recording memory writes
+    #[test]
+    fn test_write_log() {
+        let program = [1, 0, 0, 0, 1002, 4, 3, 4, 3, 11, 99];
+        let mut computer = Computer::with_input(&program, &[7]).with_write_log(true);
+        computer.run().unwrap();
+        assert_eq!(
+            computer.write_log(),
+            &[
+                WriteEvent {
+                    ip: 0,
+                    addr: 0,
+                    old: 1,
+                    new: 2
+                },
+                WriteEvent {
+                    ip: 4,
+                    addr: 4,
+                    old: 1002,
+                    new: 3006
+                },
+                WriteEvent {
+                    ip: 8,
+                    addr: 11,
+                    old: 0,
+                    new: 7
+                },
+            ]
+        );
+
+        let mut computer = Computer::with_input(&program, &[7]);
+        computer.run().unwrap();
+        assert!(computer.write_log().is_empty());
+    }
+
+    /// Test counting opcodes
+    #[test]
+    fn test_opcode_histogram() {
+        let config = Config {
+            count_opcodes: true,
+            ..Config::default()
+        };
+        // Add 3 to memory[12] until it's at least 10; the loop runs 4 times
+        let program = [1001, 12, 3, 12, 1007, 12, 10, 13, 1005, 13, 0, 99, 0, 0];
+        let mut computer = Computer::with_config(&program, config.clone());
+        computer.run().unwrap();
+        assert_eq!(computer.memory[12], 12);
+        let histogram = computer.opcode_histogram();
+        assert_eq!(histogram.get(&1), Some(&4));
+        assert_eq!(histogram.get(&7), Some(&4));
+        assert_eq!(histogram.get(&5), Some(&4));
+        assert_eq!(histogram.get(&99), Some(&1));
+        assert_eq!(histogram.get(&2), None);
+
+        let mut computer = Computer::with_config(&[1, 1, 1, 4, 99, 5, 6, 0, 99], config);
+        computer.run().unwrap();
+        let histogram = computer.opcode_histogram();
+        assert_eq!(histogram.get(&1), Some(&1));
+        assert_eq!(histogram.get(&2), Some(&1));
+
+        // Off by default
+        let mut computer = Computer::new(&program);
+        computer.run().unwrap();
+        assert!(computer.opcode_histogram().is_empty());
+    }
+
+    /// Return pseudo-random programs from a fixed seed; values are mostly
+    /// small so that valid opcodes and addresses show up often
+    fn random_programs(seed: u64, count: usize, max_len: usize) -> Vec<Vec<i64>> {
+        // xorshift64*, so the same seed always gives the same programs
+        let mut state = seed.max(1);
+        let mut next = move || {
+            state ^= state >> 12;
+            state ^= state << 25;
+            state ^= state >> 27;
+            state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+        };
+
+        (0..count)
+            .map(|_| {
+                let len = 1 + (next() % max_len as u64) as usize;
+                (0..len)
+                    .map(|_| match next() % 4 {
+                        0 => next() as i64,
+                        1 => (next() % 22210) as i64 - 10,
+                        _ => (next() % 12) as i64,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Test that random programs never panic the machine; they either halt,
+    /// run out of steps, or return an error
+    #[test]
+    fn test_random_programs() {
+        // Hand-picked programs that push values to the edges
+        let mut programs = vec![
+            vec![109, i64::MAX, 109, 1, 99],
+            vec![109, i64::MIN, 209, -1, 99],
+            vec![109, i64::MAX, 22201, 1, 1, 1, 99],
+            vec![1105, 1, i64::MAX],
+            vec![i64::MIN],
+            vec![-1],
+        ];
+        for seed in [1, 2019, 0x5eed] {
+            programs.extend(random_programs(seed, 500, 32));
+        }
 
-    'outer: for input_noun in 0..99 {
-        for input_verb in 0..99 {
-            // Replace position 1 with 12
-            prog_input[1] = input_noun as usize;
-
-            // Replace position 2 with 2
-            prog_input[2] = input_verb as usize;
-
-            let prog_output = run_program(&prog_input);
-            println!("output: {:?}", prog_output);
-            println!("position 0: {}", prog_output[0]);
-            if prog_output[0] == 19690720 {
-                println!("eureka!: noun={} verb={}", input_noun, input_verb);
-                println!("{}", 100 * input_noun + input_verb);
-                break 'outer;
+        for program in programs {
+            let mut computer = Computer::with_input(&program, &[1, -1, 0]);
+            for _ in 0..1000 {
+                if computer.halted || computer.step().is_err() {
+                    break;
+                }
             }
         }
     }
@@ -2,12 +2,12 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at https://mozilla.org/MPL/2.0/.
 
-use std::cmp::{max, min};
-use std::fs::File;
-use std::io::prelude::*;
-use std::path::Path;
+use std::cmp::{max, min, Ordering};
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::{Add, Sub};
 
-#[derive(Clone, Copy, Debug, Eq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 struct Point {
     x: i32,
     y: i32,
@@ -15,21 +15,52 @@ struct Point {
 
 impl Point {
     fn new(x: i32, y: i32) -> Point {
-        Point { x: x, y: y }
+        Point { x, y }
     }
 
     fn distance(&self, other: &Self) -> i32 {
         (self.x - other.x).abs() + (self.y - other.y).abs()
     }
 
+    #[cfg_attr(not(test), allow(dead_code))]
     fn distance_from_origin(&self) -> i32 {
         self.x.abs() + self.y.abs()
     }
+
+    /// Rotate 90 degrees clockwise about the origin
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn rotate_cw(&self) -> Point {
+        Point::new(self.y, -self.x)
+    }
+
+    /// Rotate 90 degrees counterclockwise about the origin
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn rotate_ccw(&self) -> Point {
+        Point::new(-self.y, self.x)
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+/// Translate a point by an offset
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
 }
 
-impl PartialEq for Point {
-    fn eq(&self, other: &Self) -> bool {
-        self.x == other.x && self.y == other.y
+/// Return the offset from `other` to this point
+impl Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.x - other.x, self.y - other.y)
     }
 }
 
@@ -59,12 +90,59 @@ mod test_point {
 
         assert_eq!(Point::new(-2, -2).distance_from_origin(), 4);
     }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Point::new(5, 0).to_string(), "(5, 0)");
+        assert_eq!(Point::new(-3, -12).to_string(), "(-3, -12)");
+    }
+
+    #[test]
+    fn test_sub() {
+        let p1 = Point::new(2, -3);
+        let p2 = Point::new(-1, 4);
+
+        assert_eq!(p2 - p1, Point::new(-3, 7));
+        assert_eq!(p1 - p2, Point::new(3, -7));
+        assert_eq!(p1 - p1, Point::new(0, 0));
+    }
+
+    #[test]
+    fn test_rotate() {
+        let p = Point::new(1, 0);
+        assert_eq!(p.rotate_cw(), Point::new(0, -1));
+        assert_eq!(p.rotate_ccw(), Point::new(0, 1));
+        assert_eq!(p.rotate_cw().rotate_ccw(), p);
+
+        let mut cw = p;
+        let mut ccw = p;
+        for _ in 0..4 {
+            cw = cw.rotate_cw();
+            ccw = ccw.rotate_ccw();
+        }
+        assert_eq!(cw, p);
+        assert_eq!(ccw, p);
+
+        // Distance from the origin doesn't change
+        let p = Point::new(-3, 7);
+        assert_eq!(p.rotate_cw().distance_from_origin(), 10);
+    }
+
+    #[test]
+    fn test_add() {
+        let p1 = Point::new(2, -3);
+        let p2 = Point::new(-1, 4);
+        let delta = p2 - p1;
+
+        assert_eq!(p1 + delta, p2);
+        assert_eq!(p1 + Point::new(0, 0), p1);
+    }
 }
 
 const H: i32 = 0;
 const V: i32 = 1;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 struct Line {
     start: Point,
     end: Point,
@@ -73,10 +151,7 @@ struct Line {
 impl Line {
     /// Create a new line of start and end points
     fn new(start: Point, end: Point) -> Line {
-        Line {
-            start: start,
-            end: end,
-        }
+        Line { start, end }
     }
 
     /// Return slope of this line: H or V
@@ -91,17 +166,53 @@ impl Line {
     /// Return whether given point is on this line
     fn has_point(&self, point: &Point) -> bool {
         if self.direction() == H {
-            return min(self.start.x, self.end.x) <= point.x
+            min(self.start.x, self.end.x) <= point.x
                 && max(self.start.x, self.end.x) >= point.x
-                && point.y == self.start.y;
+                && point.y == self.start.y
         } else {
-            return min(self.start.y, self.end.y) <= point.y
+            min(self.start.y, self.end.y) <= point.y
                 && max(self.start.y, self.end.y) >= point.y
-                && point.x == self.start.x;
+                && point.x == self.start.x
         }
     }
 
+    /// Return the part of this line inside the rectangle with the given min
+    /// and max corners, or None if it's entirely outside; corners given the
+    /// other way around are swapped
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn clip(&self, min: Point, max: Point) -> Option<Line> {
+        let low = Point::new(min.x.min(max.x), min.y.min(max.y));
+        let high = Point::new(min.x.max(max.x), min.y.max(max.y));
+        let clamp = |p: Point| Point::new(p.x.clamp(low.x, high.x), p.y.clamp(low.y, high.y));
+
+        // Lines are axis-aligned, so clamping the endpoints gives the clipped
+        // line as long as they're both still on this line
+        let start = clamp(self.start);
+        let end = clamp(self.end);
+        if self.has_point(&start) && self.has_point(&end) {
+            Some(Line::new(start, end))
+        } else {
+            None
+        }
+    }
+
+    /// Return which side of this line the point is on, looking from start to
+    /// end: Greater is to the left, Less is to the right, and Equal is on the
+    /// line or its extension
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn side_of(&self, point: &Point) -> Ordering {
+        let dir = self.end - self.start;
+        let offset = *point - self.start;
+        let cross = i64::from(dir.x) * i64::from(offset.y) - i64::from(dir.y) * i64::from(offset.x);
+        cross.cmp(&0)
+    }
+
     /// Return intersection point or None
+    ///
+    /// Endpoints count, so lines that touch at a corner or a T-junction
+    /// intersect there. Lines going the same way intersect only if they're
+    /// on the same row or column and meet end to end at a single point;
+    /// overlapping runs share more than one point and aren't reported.
     fn intersect(&self, other: &Self) -> Option<Point> {
         if self.direction() == H && other.direction() == V {
             // Figure out the possible intersection point; if they both have it, then yay!
@@ -111,15 +222,56 @@ impl Line {
             }
         } else if self.direction() == V && other.direction() == H {
             return other.intersect(self);
+        } else {
+            // Collinear lines meet end to end when exactly one endpoint of
+            // each is on the other and it's the same point
+            let ends_on_other: Vec<Point> = [self.start, self.end]
+                .iter()
+                .copied()
+                .filter(|p| other.has_point(p))
+                .collect();
+            let other_ends_on_self: Vec<Point> = [other.start, other.end]
+                .iter()
+                .copied()
+                .filter(|p| self.has_point(p))
+                .collect();
+            if ends_on_other.len() == 1 && other_ends_on_self == ends_on_other {
+                return Some(ends_on_other[0]);
+            }
         }
         None
     }
 }
 
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}->{}", self.start, self.end)
+    }
+}
+
 #[cfg(test)]
 mod test_line {
     use super::*;
 
+    #[test]
+    fn test_side_of() {
+        let line = Line::new(Point::new(0, 0), Point::new(5, 0));
+        assert_eq!(line.side_of(&Point::new(2, 3)), Ordering::Greater);
+        assert_eq!(line.side_of(&Point::new(2, -3)), Ordering::Less);
+        assert_eq!(line.side_of(&Point::new(2, 0)), Ordering::Equal);
+        assert_eq!(line.side_of(&Point::new(9, 0)), Ordering::Equal);
+
+        // Going the other way flips the sides
+        let line = Line::new(Point::new(5, 0), Point::new(0, 0));
+        assert_eq!(line.side_of(&Point::new(2, 3)), Ordering::Less);
+        assert_eq!(line.side_of(&Point::new(2, -3)), Ordering::Greater);
+
+        // Left of a line going up is smaller x
+        let line = Line::new(Point::new(1, 0), Point::new(1, 5));
+        assert_eq!(line.side_of(&Point::new(0, 2)), Ordering::Greater);
+        assert_eq!(line.side_of(&Point::new(3, 2)), Ordering::Less);
+    }
+
     #[test]
     fn test_direction() {
         // horizontal lines range over x
@@ -134,16 +286,92 @@ mod test_line {
         );
     }
 
+    #[test]
+    fn test_display() {
+        let line = Line::new(Point::new(0, 0), Point::new(10, 0));
+        assert_eq!(line.to_string(), "(0, 0)->(10, 0)");
+
+        let line = Line::new(Point::new(5, 4), Point::new(5, -4));
+        assert_eq!(line.to_string(), "(5, 4)->(5, -4)");
+    }
+
     #[test]
     fn test_has_point() {
         let line = Line::new(Point::new(0, 0), Point::new(10, 0));
 
-        assert_eq!(line.has_point(&Point::new(0, 0)), true);
-        assert_eq!(line.has_point(&Point::new(5, 0)), true);
-        assert_eq!(line.has_point(&Point::new(10, 0)), true);
+        assert!(line.has_point(&Point::new(0, 0)));
+        assert!(line.has_point(&Point::new(5, 0)));
+        assert!(line.has_point(&Point::new(10, 0)));
 
-        assert_eq!(line.has_point(&Point::new(-1, 0)), false);
-        assert_eq!(line.has_point(&Point::new(-1, -1)), false);
+        assert!(!line.has_point(&Point::new(-1, 0)));
+        assert!(!line.has_point(&Point::new(-1, -1)));
+    }
+
+    #[test]
+    fn test_clip_inside() {
+        let line = Line::new(Point::new(2, 1), Point::new(8, 1));
+        let clipped = line.clip(Point::new(0, 0), Point::new(10, 10)).unwrap();
+        assert_eq!((clipped.start, clipped.end), (line.start, line.end));
+    }
+
+    #[test]
+    fn test_clip_partial() {
+        let line = Line::new(Point::new(-5, 3), Point::new(15, 3));
+        let clipped = line.clip(Point::new(0, 0), Point::new(10, 10)).unwrap();
+        assert_eq!(
+            (clipped.start, clipped.end),
+            (Point::new(0, 3), Point::new(10, 3))
+        );
+
+        // Direction is kept
+        let line = Line::new(Point::new(4, 12), Point::new(4, 5));
+        let clipped = line.clip(Point::new(0, 0), Point::new(10, 10)).unwrap();
+        assert_eq!(
+            (clipped.start, clipped.end),
+            (Point::new(4, 10), Point::new(4, 5))
+        );
+
+        // Touching the edge leaves a single point
+        let line = Line::new(Point::new(10, 2), Point::new(20, 2));
+        let clipped = line.clip(Point::new(0, 0), Point::new(10, 10)).unwrap();
+        assert_eq!(
+            (clipped.start, clipped.end),
+            (Point::new(10, 2), Point::new(10, 2))
+        );
+    }
+
+    #[test]
+    fn test_clip_outside() {
+        let min = Point::new(0, 0);
+        let max = Point::new(10, 10);
+        assert!(Line::new(Point::new(20, 3), Point::new(30, 3))
+            .clip(min, max)
+            .is_none());
+        assert!(Line::new(Point::new(2, -1), Point::new(8, -1))
+            .clip(min, max)
+            .is_none());
+        assert!(Line::new(Point::new(-3, 12), Point::new(-3, -2))
+            .clip(min, max)
+            .is_none());
+    }
+
+    #[test]
+    fn test_clip_inverted_corners() {
+        let line = Line::new(Point::new(-5, 3), Point::new(15, 3));
+        for (min, max) in [
+            (Point::new(10, 10), Point::new(0, 0)),
+            (Point::new(10, 0), Point::new(0, 10)),
+            (Point::new(0, 10), Point::new(10, 0)),
+        ] {
+            let clipped = line.clip(min, max).unwrap();
+            assert_eq!(
+                (clipped.start, clipped.end),
+                (Point::new(0, 3), Point::new(10, 3))
+            );
+        }
+        assert!(Line::new(Point::new(2, -1), Point::new(8, -1))
+            .clip(Point::new(10, 10), Point::new(0, 0))
+            .is_none());
     }
 
     #[test]
@@ -156,6 +384,46 @@ mod test_line {
         assert_eq!(line2.intersect(&line1), Some(Point::new(5, 0)));
     }
 
+    #[test]
+    fn test_intersect_endpoints() {
+        // Corner to corner, either way round
+        let line1 = Line::new(Point::new(0, 5), Point::new(5, 5));
+        let line2 = Line::new(Point::new(5, 0), Point::new(5, 5));
+        assert_eq!(line1.intersect(&line2), Some(Point::new(5, 5)));
+        assert_eq!(line2.intersect(&line1), Some(Point::new(5, 5)));
+
+        // T-junction
+        let line2 = Line::new(Point::new(3, 5), Point::new(3, 9));
+        assert_eq!(line1.intersect(&line2), Some(Point::new(3, 5)));
+        assert_eq!(line2.intersect(&line1), Some(Point::new(3, 5)));
+
+        // Collinear, end to end
+        let line2 = Line::new(Point::new(9, 5), Point::new(5, 5));
+        assert_eq!(line1.intersect(&line2), Some(Point::new(5, 5)));
+        assert_eq!(line2.intersect(&line1), Some(Point::new(5, 5)));
+    }
+
+    #[test]
+    fn test_dont_intersect_collinear() {
+        let line1 = Line::new(Point::new(0, 5), Point::new(5, 5));
+
+        // Overlapping
+        let line2 = Line::new(Point::new(3, 5), Point::new(9, 5));
+        assert_eq!(line1.intersect(&line2), None);
+        assert_eq!(line2.intersect(&line1), None);
+
+        // One inside the other
+        let line2 = Line::new(Point::new(1, 5), Point::new(4, 5));
+        assert_eq!(line1.intersect(&line2), None);
+        assert_eq!(line2.intersect(&line1), None);
+
+        // Parallel with a gap, or on another row
+        let line2 = Line::new(Point::new(6, 5), Point::new(9, 5));
+        assert_eq!(line1.intersect(&line2), None);
+        let line2 = Line::new(Point::new(0, 6), Point::new(5, 6));
+        assert_eq!(line1.intersect(&line2), None);
+    }
+
     #[test]
     fn test_dont_intersect() {
         let line1 = Line::new(Point::new(0, 0), Point::new(10, 0));
@@ -167,35 +435,72 @@ mod test_line {
     }
 }
 
-/// Parses a wire into a vector of lines from the origin
-fn create_wire(data: &str) -> Vec<Line> {
-    let mut start = Point::new(0, 0);
-    let mut end: Point;
-    let mut lines = Vec::new();
-
-    for item in data.split(",") {
-        println!("{:?}: -> {}", start, item);
-        let opcode = &item[0..1];
-        let num = &item[1..].parse::<i32>().unwrap();
+/// Errors from parsing a wire's moves
+#[derive(Debug, PartialEq)]
+enum WireParseError {
+    /// The move doesn't start with one of R, L, U, or D
+    InvalidDirection(String),
+    /// The move's distance isn't a number
+    InvalidDistance(String),
+}
 
-        match &opcode as &str {
-            "R" => {
-                end = Point::new(start.x + num, start.y);
-            }
-            "L" => {
-                end = Point::new(start.x - num, start.y);
-            }
-            "U" => {
-                end = Point::new(start.x, start.y + num);
+impl fmt::Display for WireParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WireParseError::InvalidDirection(item) => {
+                write!(f, "direction not valid ({})", item)
             }
-            "D" => {
-                end = Point::new(start.x, start.y - num);
-            }
-            _ => {
-                eprintln!("Error: opcode {} not valid ({})", opcode, item);
-                std::process::exit(exitcode::DATAERR);
+            WireParseError::InvalidDistance(item) => {
+                write!(f, "distance not valid ({})", item)
             }
         }
+    }
+}
+
+/// Parses a wire into its list of (direction, distance) moves
+///
+/// The distance can have a sign; a negative distance moves the opposite way,
+/// so `R-5` is the same move as `L5`.
+fn parse_moves(data: &str) -> Result<Vec<(char, i32)>, WireParseError> {
+    data.split(',')
+        .map(|item| {
+            let mut chars = item.chars();
+            let direction = match chars.next() {
+                Some(c) if "RLUD".contains(c) => c,
+                _ => return Err(WireParseError::InvalidDirection(item.to_string())),
+            };
+            let distance = chars
+                .as_str()
+                .parse::<i32>()
+                .map_err(|_| WireParseError::InvalidDistance(item.to_string()))?;
+            Ok((direction, distance))
+        })
+        .collect()
+}
+
+/// Serializes moves back into the wire format
+#[cfg_attr(not(test), allow(dead_code))]
+fn moves_to_string(moves: &[(char, i32)]) -> String {
+    moves
+        .iter()
+        .map(|(direction, distance)| format!("{}{}", direction, distance))
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/// Converts moves into a vector of lines from the origin
+fn wire_from_moves(moves: &[(char, i32)]) -> Vec<Line> {
+    let mut start = Point::new(0, 0);
+    let mut lines = Vec::new();
+
+    for &(direction, num) in moves.iter() {
+        let delta = match direction {
+            'R' => Point::new(num, 0),
+            'L' => Point::new(-num, 0),
+            'U' => Point::new(0, num),
+            _ => Point::new(0, -num),
+        };
+        let end = start + delta;
         lines.push(Line::new(start, end));
         start = end;
     }
@@ -203,111 +508,1287 @@ fn create_wire(data: &str) -> Vec<Line> {
     lines
 }
 
-fn main() {
-    let datafile_arg = match std::env::args().nth(1) {
-        Some(path) => path,
-        None => {
-            eprintln!("Error: no data file provided.");
-            std::process::exit(exitcode::DATAERR);
-        }
-    };
-
-    let mut file = match File::open(Path::new(&datafile_arg)) {
+/// Parses a wire into a vector of lines from the origin
+fn create_wire(data: &str) -> Vec<Line> {
+    match parse_moves(data) {
+        Ok(moves) => wire_from_moves(&moves),
         Err(e) => {
-            eprintln!("Can't open file: {}", e);
-            std::process::exit(exitcode::DATAERR);
+            let e = common::AocError::Data(e.to_string());
+            eprintln!("Error: {}", e);
+            std::process::exit(common::exit_code_for(&e));
         }
-        Ok(file) => file,
-    };
+    }
+}
 
-    let mut contents = String::new();
-    match file.read_to_string(&mut contents) {
-        Ok(_) => (),
-        Err(e) => {
-            eprintln!("Can't read file: {}", e);
-            std::process::exit(exitcode::DATAERR);
+/// Parses the data file contents into wires, one per line, ignoring blank lines
+/// and Windows line endings
+fn parse_wires(contents: &str) -> Vec<Vec<Line>> {
+    contents
+        .split('\n')
+        .map(|line| line.trim_end_matches('\r').trim())
+        .filter(|line| !line.is_empty())
+        .map(create_wire)
+        .collect()
+}
+
+/// Lazily yield the points where the two wires cross, excluding the origin.
+/// Where both wires turn a corner at the same point, two pairs of segments
+/// find the same crossing, so a point can come up more than once.
+fn intersections_iter<'a>(
+    wire_a: &'a [Line],
+    wire_b: &'a [Line],
+) -> impl Iterator<Item = Point> + 'a {
+    let origin = Point::new(0, 0);
+    wire_a
+        .iter()
+        .flat_map(move |line_a| {
+            wire_b
+                .iter()
+                .filter_map(move |line_b| line_a.intersect(line_b))
+        })
+        .filter(move |&p| p != origin)
+}
+
+/// Return a copy of the wire with every line shifted by the delta
+#[cfg_attr(not(test), allow(dead_code))]
+fn translate_wire(wire: &[Line], dx: i32, dy: i32) -> Vec<Line> {
+    let delta = Point::new(dx, dy);
+    wire.iter()
+        .map(|line| Line::new(line.start + delta, line.end + delta))
+        .collect()
+}
+
+/// Return the wire walked backwards from its end to its start
+#[cfg_attr(not(test), allow(dead_code))]
+fn reverse_wire(wire: &[Line]) -> Vec<Line> {
+    wire.iter()
+        .rev()
+        .map(|line| Line::new(line.end, line.start))
+        .collect()
+}
+
+/// Return whether the two wires are made of the same segments in the same
+/// order
+#[cfg_attr(not(test), allow(dead_code))]
+fn same_path(a: &[Line], b: &[Line]) -> bool {
+    a == b
+}
+
+/// Return whether the boxes around the two wires' segment endpoints overlap;
+/// if they don't, the wires can't cross. Empty wires overlap nothing.
+fn wires_overlap_bbox(a: &[Line], b: &[Line]) -> bool {
+    match (endpoint_bounds(a.iter()), endpoint_bounds(b.iter())) {
+        (Some((a_low, a_high)), Some((b_low, b_high))) => {
+            a_low.x <= b_high.x && b_low.x <= a_high.x && a_low.y <= b_high.y && b_low.y <= a_high.y
         }
-    };
+        _ => false,
+    }
+}
 
-    // Parse the wires
-    let mut wires = Vec::new();
-    let contents = contents.trim();
-    for line in contents.split("\n") {
-        let line = line.trim();
-        println!("WIRE: {}", line);
-        // Parse wire and convert it to a series of Lines
-        wires.push(create_wire(line));
-        println!("parsed: {:?}", wires[wires.len() - 1]);
+/// Return each crossing of the two wires with the indexes of the segment in
+/// each wire that cross there, excluding the origin; a crossing at a corner
+/// is listed for every pair of segments that meet there
+#[cfg_attr(not(test), allow(dead_code))]
+fn intersections_detailed(a: &[Line], b: &[Line]) -> Vec<(usize, usize, Point)> {
+    let origin = Point::new(0, 0);
+    let mut crossings = Vec::new();
+    for (a_i, line_a) in a.iter().enumerate() {
+        for (b_i, line_b) in b.iter().enumerate() {
+            match line_a.intersect(line_b) {
+                Some(p) if p != origin => crossings.push((a_i, b_i, p)),
+                _ => {}
+            }
+        }
     }
+    crossings
+}
+
+/// Return the distinct points where different wires cross, excluding the
+/// origin
+fn find_intersections(wires: &[Vec<Line>]) -> Vec<Point> {
+    intersections_skip_first(wires, 0)
+}
 
+/// Return the distinct points where different wires cross, excluding the
+/// origin and ignoring the first `skip` segments of each wire
+fn intersections_skip_first(wires: &[Vec<Line>], skip: usize) -> Vec<Point> {
     let mut intersections = Vec::new();
 
-    // For each line in each wire, check intersections with other wire
-    'outer: for wire_lhs_i in 0..wires.len() {
-        for wire_rhs_i in 0..wires.len() {
-            if wire_lhs_i == wire_rhs_i {
-                continue 'outer;
+    for (wire_lhs_i, wire_lhs) in wires.iter().enumerate() {
+        for wire_rhs in wires[wire_lhs_i + 1..].iter() {
+            let wire_lhs = wire_lhs.get(skip..).unwrap_or(&[]);
+            let wire_rhs = wire_rhs.get(skip..).unwrap_or(&[]);
+            if !wires_overlap_bbox(wire_lhs, wire_rhs) {
+                continue;
             }
-            let wire_lhs = &wires[wire_lhs_i];
-            let wire_rhs = &wires[wire_rhs_i];
-
-            for line_lhs in wire_lhs.iter() {
-                for line_rhs in wire_rhs.iter() {
-                    match line_lhs.intersect(&line_rhs) {
-                        Some(p) => intersections.push(p),
-                        None => (),
-                    }
+            for p in intersections_iter(wire_lhs, wire_rhs) {
+                if !intersections.contains(&p) {
+                    intersections.push(p);
                 }
             }
         }
     }
-    println!("intersections: {:?}", intersections);
 
-    // For each intersection, figure out the distance from origin and then figure out the minimum
-    // distance
-    let distances_from_origin: Vec<i32> = intersections
-        .iter()
-        .map(|&val| val.distance_from_origin())
+    intersections
+}
+
+/// Lazily yield every grid cell along the wire in walk order with the number
+/// of steps it takes to get there, starting with its first point at 0 steps;
+/// the corner where two lines meet comes up once
+#[cfg_attr(not(test), allow(dead_code))]
+fn walk_iter(wire: &[Line]) -> impl Iterator<Item = (Point, i32)> + '_ {
+    wire.iter()
+        .enumerate()
+        .flat_map(|(i, line)| {
+            let step = Point::new(
+                (line.end.x - line.start.x).signum(),
+                (line.end.y - line.start.y).signum(),
+            );
+            // The previous line already ended on this one's start
+            let first = if i == 0 { 0 } else { 1 };
+            (first..=line.start.distance(&line.end))
+                .map(move |k| line.start + Point::new(step.x * k, step.y * k))
+        })
+        .zip(0..)
+}
+
+/// Return every grid cell the wire passes through in walk order, starting
+/// with its first point; the corner where two lines meet is listed once
+#[cfg_attr(not(test), allow(dead_code))]
+fn occupied_cells(wire: &[Line]) -> Vec<Point> {
+    walk_iter(wire).map(|(p, _)| p).collect()
+}
+
+/// Return the cells wire `a` passes through that wire `b` doesn't, in the
+/// order `a` reaches them; cells `a` crosses twice are listed once
+#[cfg_attr(not(test), allow(dead_code))]
+fn cells_only_in_first(a: &[Line], b: &[Line]) -> Vec<Point> {
+    let mut seen: HashSet<Point> = occupied_cells(b).into_iter().collect();
+    occupied_cells(a)
+        .into_iter()
+        .filter(|&p| seen.insert(p))
+        .collect()
+}
+
+/// Return the set of grid cells the wire passes through, excluding the origin
+#[cfg_attr(not(test), allow(dead_code))]
+fn wire_cells(wire: &[Line]) -> HashSet<Point> {
+    let origin = Point::new(0, 0);
+    occupied_cells(wire)
+        .into_iter()
+        .filter(|&p| p != origin)
+        .collect()
+}
+
+/// Return the points where the two wires cross by filling in every cell each
+/// wire passes through and intersecting the sets; sorted by x then y. Unlike
+/// find_intersections, every cell along a stretch where the wires run on top
+/// of each other counts, not just the crossings.
+#[cfg_attr(not(test), allow(dead_code))]
+fn intersections_gridfill(wire_a: &[Line], wire_b: &[Line]) -> Vec<Point> {
+    let cells_b = wire_cells(wire_b);
+    let mut intersections: Vec<Point> = wire_cells(wire_a)
+        .into_iter()
+        .filter(|p| cells_b.contains(p))
         .collect();
+    intersections.sort_by_key(|p| (p.x, p.y));
+    intersections
+}
 
-    println!("distances from origin: {:?}", distances_from_origin);
+/// Return the points where different wires cross on a torus of the given size,
+/// where a wire going off one edge comes back on the opposite edge. Points
+/// are normalized into `0..width` and `0..height`, sorted by x then y, and
+/// exclude the origin. A torus without a positive width and height has no
+/// points, so nothing crosses on it.
+#[cfg_attr(not(test), allow(dead_code))]
+fn intersections_toroidal(wires: &[Vec<Line>], width: i32, height: i32) -> Vec<Point> {
+    if width <= 0 || height <= 0 {
+        return vec![];
+    }
+    let origin = Point::new(0, 0);
+    let cells: Vec<HashSet<Point>> = wires
+        .iter()
+        .map(|wire| {
+            wire_cells(wire)
+                .into_iter()
+                .map(|p| Point::new(p.x.rem_euclid(width), p.y.rem_euclid(height)))
+                .filter(|&p| p != origin)
+                .collect()
+        })
+        .collect();
 
-    match distances_from_origin.iter().min() {
-        Some(val) => {
-            println!("Minimum distance from origin: {}", val);
+    let mut intersections = HashSet::new();
+    for (i, cells_lhs) in cells.iter().enumerate() {
+        for cells_rhs in cells[i + 1..].iter() {
+            intersections.extend(cells_lhs.intersection(cells_rhs));
         }
-        None => {
-            println!("No minimum distance from origin was found.");
+    }
+
+    let mut intersections: Vec<Point> = intersections.into_iter().collect();
+    intersections.sort_by_key(|p| (p.x, p.y));
+    intersections
+}
+
+/// Return the number of distinct points where different wires cross,
+/// excluding the origin
+#[cfg_attr(not(test), allow(dead_code))]
+fn intersection_count(wires: &[Vec<Line>]) -> usize {
+    find_intersections(wires).len()
+}
+
+/// Return an N by N matrix of how many distinct points each pair of wires
+/// cross at, excluding the origin; a wire isn't counted as crossing itself,
+/// so the diagonal is zero
+#[cfg_attr(not(test), allow(dead_code))]
+fn pairwise_intersection_counts(wires: &[Vec<Line>]) -> Vec<Vec<usize>> {
+    let mut counts = vec![vec![0; wires.len()]; wires.len()];
+    for i in 0..wires.len() {
+        for j in i + 1..wires.len() {
+            let crossings: HashSet<Point> = intersections_iter(&wires[i], &wires[j]).collect();
+            counts[i][j] = crossings.len();
+            counts[j][i] = crossings.len();
         }
     }
+    counts
+}
 
-    // For each intersection, figure out the number of steps for each wire and add
-    let mut steps = Vec::new();
-    for point in intersections.iter() {
-        let mut total_steps = 0;
-        for wire in wires.iter() {
-            let mut steps = 0;
-            for line in wire.iter() {
-                if line.has_point(&point) {
-                    steps += line.start.distance(&point);
-                    break;
-                } else {
-                    steps += line.start.distance(&line.end);
-                }
+/// Return the intersections within the given Manhattan distance of the origin
+#[cfg_attr(not(test), allow(dead_code))]
+fn intersections_within(wires: &[Vec<Line>], radius: i32) -> Vec<Point> {
+    find_intersections(wires)
+        .into_iter()
+        .filter(|p| p.distance_from_origin() <= radius)
+        .collect()
+}
+
+/// Return the average position of the crossings, excluding the origin, or None
+/// if the wires don't cross
+#[cfg_attr(not(test), allow(dead_code))]
+fn intersections_centroid(wires: &[Vec<Line>]) -> Option<(f64, f64)> {
+    let crossings = find_intersections(wires);
+    if crossings.is_empty() {
+        return None;
+    }
+    let count = crossings.len() as f64;
+    let (x, y) = crossings.iter().fold((0.0, 0.0), |(x, y), p| {
+        (x + f64::from(p.x), y + f64::from(p.y))
+    });
+    Some((x / count, y / count))
+}
+
+/// Group crossings that are within the tolerance of each other, chaining, and
+/// return the first crossing found in each group; with a tolerance of 0 this
+/// is the same as find_intersections
+#[cfg_attr(not(test), allow(dead_code))]
+fn intersections_snapped(wires: &[Vec<Line>], tolerance: i32) -> Vec<Point> {
+    let crossings = find_intersections(wires);
+    // Each group is the indexes of its crossings, smallest first
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for (i, p) in crossings.iter().enumerate() {
+        let (near, mut far): (Vec<_>, Vec<_>) = groups
+            .into_iter()
+            .partition(|group| group.iter().any(|&j| crossings[j].distance(p) <= tolerance));
+        let mut merged: Vec<usize> = near.into_iter().flatten().collect();
+        merged.sort_unstable();
+        merged.push(i);
+        far.push(merged);
+        groups = far;
+    }
+    let mut firsts: Vec<usize> = groups.iter().map(|group| group[0]).collect();
+    firsts.sort_unstable();
+    firsts.into_iter().map(|i| crossings[i]).collect()
+}
+
+/// Return the intersection closest to the reference point and its distance
+/// from it
+fn nearest_intersection_to(wires: &[Vec<Line>], reference: &Point) -> Option<(Point, i32)> {
+    find_intersections(wires)
+        .into_iter()
+        .map(|p| (p, p.distance(reference)))
+        .min_by_key(|&(_, distance)| distance)
+}
+
+/// Return the intersection closest to the origin and its distance
+fn closest_intersection(wires: &[Vec<Line>]) -> Option<(Point, i32)> {
+    nearest_intersection_to(wires, &Point::new(0, 0))
+}
+
+/// Return the intersections paired with their distance from the origin,
+/// closest first; ties are ordered by x then y
+#[cfg_attr(not(test), allow(dead_code))]
+fn intersections_sorted(wires: &[Vec<Line>]) -> Vec<(Point, i32)> {
+    let mut intersections: Vec<(Point, i32)> = find_intersections(wires)
+        .into_iter()
+        .map(|p| (p, p.distance_from_origin()))
+        .collect();
+    intersections.sort_by_key(|&(p, distance)| (distance, p.x, p.y));
+    intersections
+}
+
+/// Return the smallest distance between two different intersections, or None
+/// if there are fewer than two
+#[cfg_attr(not(test), allow(dead_code))]
+fn closest_crossing_pair_distance(wires: &[Vec<Line>]) -> Option<i32> {
+    let points = find_intersections(wires);
+    points
+        .iter()
+        .enumerate()
+        .flat_map(|(i, a)| points[i + 1..].iter().map(move |b| a.distance(b)))
+        .min()
+}
+
+/// Return the convex hull of the intersections counterclockwise, starting
+/// from the one with the smallest x (then y); points on an edge of the hull
+/// are left out
+#[cfg_attr(not(test), allow(dead_code))]
+fn intersection_hull(wires: &[Vec<Line>]) -> Vec<Point> {
+    let mut points = find_intersections(wires);
+    points.sort_by_key(|p| (p.x, p.y));
+    if points.len() < 3 {
+        return points;
+    }
+
+    // Monotone chain: build the lower then the upper hull, dropping points
+    // that don't turn counterclockwise
+    let cross = |o: Point, a: Point, b: Point| {
+        i64::from(a.x - o.x) * i64::from(b.y - o.y) - i64::from(a.y - o.y) * i64::from(b.x - o.x)
+    };
+    let mut hull: Vec<Point> = Vec::new();
+    for pass in 0..2 {
+        let start = hull.len();
+        let ordered: Vec<Point> = if pass == 0 {
+            points.clone()
+        } else {
+            points.iter().rev().copied().collect()
+        };
+        for p in ordered {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0
+            {
+                hull.pop();
             }
-            total_steps += steps;
+            hull.push(p);
         }
-        steps.push(total_steps);
+        // The last point is the first point of the other half
+        hull.pop();
     }
+    hull
+}
 
-    println!("Total steps to intersections: {:?}", steps);
+/// Return the (min, max) corners of the box containing every segment endpoint,
+/// or None if there are no segments
+#[cfg_attr(not(test), allow(dead_code))]
+fn wires_bounds(wires: &[Vec<Line>]) -> Option<(Point, Point)> {
+    endpoint_bounds(wires.iter().flatten())
+}
 
-    match steps.iter().min() {
-        Some(val) => {
-            println!("Minimum steps: {}", val);
+/// Return the (min, max) corners of the box containing the endpoints of the
+/// lines, or None if there aren't any
+fn endpoint_bounds<'a, I: Iterator<Item = &'a Line>>(lines: I) -> Option<(Point, Point)> {
+    let mut points = lines.flat_map(|line| [line.start, line.end]);
+    let first = points.next()?;
+    Some(points.fold((first, first), |(low, high), p| {
+        (
+            Point::new(min(low.x, p.x), min(low.y, p.y)),
+            Point::new(max(high.x, p.x), max(high.y, p.y)),
+        )
+    }))
+}
+
+/// Colors for the wires in render_svg, reused when there are more wires
+#[cfg_attr(not(test), allow(dead_code))]
+const WIRE_COLORS: [&str; 6] = ["red", "blue", "green", "orange", "purple", "teal"];
+
+/// Return an SVG document drawing each wire as a polyline in its own color,
+/// the crossings as circles, and the origin as a square. Each polyline starts
+/// where its wire does, and empty wires are left out. The view fits the wires
+/// and the origin with a one unit margin; y is flipped so up is up.
+#[cfg_attr(not(test), allow(dead_code))]
+fn render_svg(wires: &[Vec<Line>], intersections: &[Point]) -> String {
+    let origin = Point::new(0, 0);
+    let (low, high) = wires_bounds(wires).unwrap_or((origin, origin));
+    let low = Point::new(min(low.x, 0), min(low.y, 0));
+    let high = Point::new(max(high.x, 0), max(high.y, 0));
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n",
+        low.x - 1,
+        -high.y - 1,
+        high.x - low.x + 2,
+        high.y - low.y + 2
+    );
+    for (i, wire) in wires.iter().enumerate() {
+        let start = match wire.first() {
+            Some(line) => line.start,
+            None => continue,
+        };
+        let points: Vec<String> = std::iter::once(start)
+            .chain(wire.iter().map(|line| line.end))
+            .map(|p| format!("{},{}", p.x, -p.y))
+            .collect();
+        svg.push_str(&format!(
+            "  <polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"0.5\"/>\n",
+            points.join(" "),
+            WIRE_COLORS[i % WIRE_COLORS.len()]
+        ));
+    }
+    for p in intersections {
+        svg.push_str(&format!(
+            "  <circle cx=\"{}\" cy=\"{}\" r=\"1\" fill=\"black\"/>\n",
+            p.x, -p.y
+        ));
+    }
+    svg.push_str("  <rect x=\"-1\" y=\"-1\" width=\"2\" height=\"2\" fill=\"black\"/>\n");
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Return the number of steps it takes to walk the whole wire. Segments are
+/// axis-aligned, so this is also the wire's total Manhattan length.
+fn walk_length(wire: &[Line]) -> i32 {
+    wire.iter().map(|line| line.start.distance(&line.end)).sum()
+}
+
+/// Return the total length of all the wires together
+#[cfg_attr(not(test), allow(dead_code))]
+fn combined_length(wires: &[Vec<Line>]) -> i32 {
+    wires.iter().map(|wire| walk_length(wire)).sum()
+}
+
+/// Return the number of steps along the wire to first reach the point, or None
+/// if the point isn't on the wire
+fn steps_along(wire: &[Line], target: &Point) -> Option<i32> {
+    let mut steps = 0;
+    for line in wire.iter() {
+        if line.has_point(target) {
+            return Some(steps + line.start.distance(target));
+        }
+        steps += line.start.distance(&line.end);
+    }
+    None
+}
+
+/// Return the number of steps along the wire to first reach the point, or the
+/// whole wire's length if the point isn't on it
+fn steps_to_point(wire: &[Line], point: &Point) -> i32 {
+    steps_along(wire, point).unwrap_or_else(|| walk_length(wire))
+}
+
+/// Return each intersection with the combined steps along the wires that
+/// cross there
+fn intersection_steps(wires: &[Vec<Line>]) -> Vec<(Point, i32)> {
+    find_intersections(wires)
+        .into_iter()
+        .map(|p| {
+            // Only count the wires that actually cross at this point
+            let steps = wires
+                .iter()
+                .filter(|wire| wire.iter().any(|line| line.has_point(&p)))
+                .map(|wire| steps_to_point(wire, &p))
+                .sum();
+            (p, steps)
+        })
+        .collect()
+}
+
+/// Return the intersection reachable in the fewest combined steps along the
+/// wires that cross there, and that step count
+fn fewest_steps_intersection(wires: &[Vec<Line>]) -> Option<(Point, i32)> {
+    intersection_steps(wires)
+        .into_iter()
+        .min_by_key(|&(_, steps)| steps)
+}
+
+/// Return the intersection reachable in the most combined steps along the
+/// wires that cross there, and that step count
+#[cfg_attr(not(test), allow(dead_code))]
+fn most_steps_intersection(wires: &[Vec<Line>]) -> Option<(Point, i32)> {
+    intersection_steps(wires)
+        .into_iter()
+        .max_by_key(|&(_, steps)| steps)
+}
+
+/// Day 3 solution; the input is the wires, one per line
+#[cfg_attr(not(test), allow(dead_code))]
+struct Day3;
+
+impl common::Solution for Day3 {
+    fn part1(&self, input: &str) -> String {
+        match closest_intersection(&parse_wires(input)) {
+            Some((_, distance)) => distance.to_string(),
+            None => "none".to_string(),
+        }
+    }
+
+    fn part2(&self, input: &str) -> String {
+        match fewest_steps_intersection(&parse_wires(input)) {
+            Some((_, steps)) => steps.to_string(),
+            None => "none".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_wires {
+    use super::*;
+
+    /// Wires from the first example in the puzzle
+    fn example_wires() -> Vec<Vec<Line>> {
+        vec![
+            create_wire("R75,D30,R83,U83,L12,D49,R71,U7,L72"),
+            create_wire("U62,R66,U55,R34,D71,R55,D58,R83"),
+        ]
+    }
+
+    #[test]
+    fn test_parse_moves() {
+        assert_eq!(
+            parse_moves("R8,U5,L5,D3"),
+            Ok(vec![('R', 8), ('U', 5), ('L', 5), ('D', 3)])
+        );
+        assert_eq!(
+            parse_moves("R8,X5"),
+            Err(WireParseError::InvalidDirection("X5".to_string()))
+        );
+        assert_eq!(
+            parse_moves("R8,U"),
+            Err(WireParseError::InvalidDistance("U".to_string()))
+        );
+        assert_eq!(
+            parse_moves(""),
+            Err(WireParseError::InvalidDirection("".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_moves_signed() {
+        assert_eq!(parse_moves("R-5,U+3"), Ok(vec![('R', -5), ('U', 3)]));
+
+        let signed = create_wire("R-5,D-2");
+        let unsigned = create_wire("L5,U2");
+        assert_eq!(signed.len(), unsigned.len());
+        for (lhs, rhs) in signed.iter().zip(unsigned.iter()) {
+            assert_eq!((lhs.start, lhs.end), (rhs.start, rhs.end));
         }
-        None => {
-            println!("No minimum steps was found.");
+        assert_eq!(walk_length(&signed), 7);
+    }
+
+    #[test]
+    fn test_moves_round_trip() -> Result<(), WireParseError> {
+        assert_eq!(moves_to_string(&parse_moves("R8,U5,L5,D3")?), "R8,U5,L5,D3");
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_wires() {
+        let wires = parse_wires("R8,U5,L5,D3\nU7,R6,D4,L4\n");
+        assert_eq!(wires.len(), 2);
+        assert_eq!(wires[0].len(), 4);
+        assert_eq!(wires[1][3].end, Point::new(2, 3));
+    }
+
+    #[test]
+    fn test_parse_wires_from_reader() {
+        let contents =
+            common::read_from(std::io::Cursor::new("R8,U5,L5,D3\nU7,R6,D4,L4\n")).unwrap();
+        let wires = parse_wires(&contents);
+        assert_eq!(wires.len(), 2);
+        assert_eq!(wires[0][3].end, Point::new(3, 2));
+    }
+
+    #[test]
+    fn test_parse_wires_crlf() {
+        let wires = parse_wires("R8,U5,L5,D3\r\nU7,R6,D4,L4\r\n\r\n");
+        assert_eq!(wires.len(), 2);
+        assert_eq!(wires[0][3].end, Point::new(3, 2));
+        assert_eq!(wires[1][3].end, Point::new(2, 3));
+    }
+
+    #[test]
+    fn test_find_intersections_excludes_origin() {
+        let wires = vec![create_wire("R8,U5,L5,D3"), create_wire("U7,R6,D4,L4")];
+        let mut intersections = find_intersections(&wires);
+        intersections.sort_by_key(|p| (p.x, p.y));
+        assert_eq!(intersections, vec![Point::new(3, 3), Point::new(6, 5)]);
+    }
+
+    #[test]
+    fn test_intersections_iter() {
+        let wires = example_wires();
+        let lazy: Vec<Point> = intersections_iter(&wires[0], &wires[1]).collect();
+        assert_eq!(lazy, find_intersections(&wires));
+
+        let closest = intersections_iter(&wires[0], &wires[1])
+            .map(|p| p.distance_from_origin())
+            .min();
+        assert_eq!(closest, Some(159));
+    }
+
+    #[test]
+    fn test_reverse_wire() {
+        let wire = create_wire("R8,U5,L5,D3");
+        let reversed = reverse_wire(&wire);
+        assert_eq!(
+            (reversed[0].start, reversed[0].end),
+            (Point::new(3, 2), Point::new(3, 5))
+        );
+        assert_eq!(reversed.last().unwrap().end, Point::new(0, 0));
+        assert!(same_path(&reverse_wire(&reversed), &wire));
+
+        // Same cells, but counted from the other end
+        assert_eq!(wire_cells(&reversed), wire_cells(&wire));
+        assert_eq!(steps_along(&wire, &Point::new(8, 0)), Some(8));
+        assert_eq!(steps_along(&reversed, &Point::new(8, 0)), Some(13));
+    }
+
+    #[test]
+    fn test_same_path() {
+        assert!(same_path(
+            &create_wire("R8,U5,L5"),
+            &create_wire("R8,U5,L5")
+        ));
+        assert!(!same_path(
+            &create_wire("R8,U5,L5"),
+            &create_wire("R8,U6,L5")
+        ));
+        assert!(!same_path(&create_wire("R8,U5"), &create_wire("R8,U5,L5")));
+        assert!(same_path(&[], &[]));
+    }
+
+    #[test]
+    fn test_translate_wire() {
+        let wire = translate_wire(&create_wire("R8,U5"), 2, -3);
+        assert_eq!(
+            (wire[0].start, wire[0].end),
+            (Point::new(2, -3), Point::new(10, -3))
+        );
+        assert_eq!(
+            (wire[1].start, wire[1].end),
+            (Point::new(10, -3), Point::new(10, 2))
+        );
+
+        // The crossings move with the wires; the shared starting point isn't
+        // the origin anymore, so it counts too
+        let wires = example_wires();
+        let moved: Vec<Vec<Line>> = wires
+            .iter()
+            .map(|wire| translate_wire(wire, 10, -7))
+            .collect();
+        let start = Point::new(10, -7);
+        let mut expected: Vec<Point> = find_intersections(&wires)
+            .into_iter()
+            .map(|p| p + start)
+            .collect();
+        expected.push(start);
+        expected.sort_by_key(|p| (p.x, p.y));
+        let mut actual = find_intersections(&moved);
+        actual.sort_by_key(|p| (p.x, p.y));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_intersections_detailed() {
+        let a = create_wire("R8,U5,L5,D3");
+        let b = create_wire("U7,R6,D4,L4");
+        assert_eq!(
+            intersections_detailed(&a, &b),
+            vec![(2, 2, Point::new(6, 5)), (3, 3, Point::new(3, 3))]
+        );
+
+        // Both wires turn at (2, 2), so every pair of segments meeting there
+        // shows up
+        let a = create_wire("R2,U2,R2");
+        let b = create_wire("U2,R2,U2");
+        assert_eq!(
+            intersections_detailed(&a, &b),
+            vec![
+                (1, 1, Point::new(2, 2)),
+                (1, 2, Point::new(2, 2)),
+                (2, 1, Point::new(2, 2)),
+                (2, 2, Point::new(2, 2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wires_overlap_bbox() {
+        let wires = example_wires();
+        assert!(wires_overlap_bbox(&wires[0], &wires[1]));
+
+        // Moved far enough apart that the boxes don't touch
+        let wires = vec![
+            create_wire("R8,U5,L5,D3"),
+            translate_wire(&create_wire("U7,R6,D4,L4"), 1000, 0),
+        ];
+        assert!(!wires_overlap_bbox(&wires[0], &wires[1]));
+        assert_eq!(find_intersections(&wires), vec![]);
+
+        // Boxes that only share a corner still overlap
+        let wires = vec![
+            create_wire("R5,U5"),
+            translate_wire(&create_wire("R5,U5"), 5, 5),
+        ];
+        assert!(wires_overlap_bbox(&wires[0], &wires[1]));
+        assert_eq!(find_intersections(&wires), vec![Point::new(5, 5)]);
+
+        assert!(!wires_overlap_bbox(&wires[0], &[]));
+    }
+
+    #[test]
+    fn test_walk_iter() {
+        let wire = create_wire("R2,U2,L1");
+        let steps: Vec<(Point, i32)> = walk_iter(&wire).take(5).collect();
+        assert_eq!(
+            steps,
+            vec![
+                (Point::new(0, 0), 0),
+                (Point::new(1, 0), 1),
+                (Point::new(2, 0), 2),
+                (Point::new(2, 1), 3),
+                (Point::new(2, 2), 4),
+            ]
+        );
+        assert_eq!(walk_iter(&wire).last(), Some((Point::new(1, 2), 5)));
+
+        // The step counts agree with steps_along
+        let wire = create_wire("R8,U5,L5,D3");
+        assert!(walk_iter(&wire).all(|(p, steps)| steps_along(&wire, &p).unwrap() <= steps));
+        assert_eq!(
+            walk_iter(&wire).find(|&(p, _)| p == Point::new(3, 3)),
+            Some((Point::new(3, 3), 20))
+        );
+    }
+
+    #[test]
+    fn test_occupied_cells() {
+        let wire = create_wire("R2,U3");
+        assert_eq!(
+            occupied_cells(&wire),
+            vec![
+                Point::new(0, 0),
+                Point::new(1, 0),
+                Point::new(2, 0),
+                Point::new(2, 1),
+                Point::new(2, 2),
+                Point::new(2, 3),
+            ]
+        );
+
+        // Every step is one cell, plus the starting point
+        let wires = example_wires();
+        assert_eq!(
+            occupied_cells(&wires[1]).len() as i32,
+            walk_length(&wires[1]) + 1
+        );
+
+        assert_eq!(occupied_cells(&[]), vec![]);
+    }
+
+    #[test]
+    fn test_cells_only_in_first() {
+        let a = create_wire("R3,U2");
+        let b = create_wire("R1,U1,R2");
+        assert_eq!(
+            cells_only_in_first(&a, &b),
+            vec![Point::new(2, 0), Point::new(3, 0), Point::new(3, 2)]
+        );
+        assert_eq!(
+            cells_only_in_first(&b, &a),
+            vec![Point::new(1, 1), Point::new(2, 1)]
+        );
+
+        // A wire that loops back over itself lists each cell once
+        let a = create_wire("U2,R1,D1,L2");
+        assert_eq!(
+            cells_only_in_first(&a, &create_wire("R1")),
+            vec![
+                Point::new(0, 1),
+                Point::new(0, 2),
+                Point::new(1, 2),
+                Point::new(1, 1),
+                Point::new(-1, 1),
+            ]
+        );
+
+        assert_eq!(cells_only_in_first(&b, &b), vec![]);
+    }
+
+    #[test]
+    fn test_intersections_gridfill() {
+        let wires = example_wires();
+        let mut intersections = find_intersections(&wires);
+        intersections.sort_by_key(|p| (p.x, p.y));
+        assert_eq!(intersections_gridfill(&wires[0], &wires[1]), intersections);
+
+        // Where the wires run along each other, every shared cell counts here,
+        // but find_intersections only has the one where b comes down onto a
+        let a = wire_from_moves(&[('R', 5)]);
+        let b = wire_from_moves(&[('U', 1), ('R', 1), ('D', 1), ('R', 3)]);
+        assert_eq!(
+            intersections_gridfill(&a, &b),
+            vec![
+                Point::new(1, 0),
+                Point::new(2, 0),
+                Point::new(3, 0),
+                Point::new(4, 0)
+            ]
+        );
+        assert_eq!(find_intersections(&[a, b]), vec![Point::new(1, 0)]);
+    }
+
+    #[test]
+    fn test_closest_intersection() {
+        let (_, distance) = closest_intersection(&example_wires()).unwrap();
+        assert_eq!(distance, 159);
+
+        let wires = vec![create_wire("R8,U5,L5,D3"), create_wire("U7,R6,D4,L4")];
+        assert_eq!(closest_intersection(&wires), Some((Point::new(3, 3), 6)));
+    }
+
+    #[test]
+    fn test_combined_length() {
+        let wires = example_wires();
+        assert_eq!(
+            combined_length(&wires),
+            walk_length(&wires[0]) + walk_length(&wires[1])
+        );
+        assert_eq!(
+            combined_length(&[create_wire("R8,U5"), create_wire("L3")]),
+            16
+        );
+        assert_eq!(combined_length(&[]), 0);
+    }
+
+    #[test]
+    fn test_nearest_intersection_to() {
+        let wires = vec![create_wire("R8,U5,L5,D3"), create_wire("U7,R6,D4,L4")];
+        assert_eq!(
+            nearest_intersection_to(&wires, &Point::new(6, 6)),
+            Some((Point::new(6, 5), 1))
+        );
+        assert_eq!(
+            nearest_intersection_to(&wires, &Point::new(0, 0)),
+            closest_intersection(&wires)
+        );
+
+        let wires = vec![create_wire("R8"), create_wire("U8")];
+        assert_eq!(nearest_intersection_to(&wires, &Point::new(3, 3)), None);
+    }
+
+    #[test]
+    fn test_intersections_sorted() {
+        let wires = vec![create_wire("R8,U5,L5,D3"), create_wire("U7,R6,D4,L4")];
+        assert_eq!(
+            intersections_sorted(&wires),
+            vec![(Point::new(3, 3), 6), (Point::new(6, 5), 11)]
+        );
+
+        let wires = example_wires();
+        let sorted = intersections_sorted(&wires);
+        assert_eq!(sorted.len(), 4);
+        assert_eq!(sorted.first().copied(), closest_intersection(&wires));
+        assert!(sorted.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+    }
+
+    #[test]
+    fn test_closest_crossing_pair_distance() {
+        // Crossings at (158, -12), (146, 46), (155, 4), and (155, 11)
+        assert_eq!(closest_crossing_pair_distance(&example_wires()), Some(7));
+
+        // The middle crossing is closer to the left one than the right one
+        let wires = vec![create_wire("U2,R10"), create_wire("R1,U4,R2,D4,R6,U4")];
+        assert_eq!(
+            find_intersections(&wires),
+            vec![Point::new(1, 2), Point::new(3, 2), Point::new(9, 2)]
+        );
+        assert_eq!(closest_crossing_pair_distance(&wires), Some(2));
+
+        let wires = vec![create_wire("R8"), create_wire("U2,R4,D4")];
+        assert_eq!(find_intersections(&wires).len(), 1);
+        assert_eq!(closest_crossing_pair_distance(&wires), None);
+    }
+
+    #[test]
+    fn test_intersection_hull() {
+        // The second wire's upright lines at x = 1, 3, and 5 cross the first
+        // wire's rows at y = 4 and 0, and all but x = 1 reach y = -2
+        let wires = vec![
+            create_wire("U4,R6,D6,L6,U2,R6"),
+            create_wire("R1,U6,R2,D8,R2,U8"),
+        ];
+        assert_eq!(find_intersections(&wires).len(), 8);
+        assert_eq!(
+            intersection_hull(&wires),
+            vec![
+                Point::new(1, 0),
+                Point::new(3, -2),
+                Point::new(5, -2),
+                Point::new(5, 4),
+                Point::new(1, 4),
+            ]
+        );
+
+        let wires = vec![create_wire("R8,U5,L5,D3"), create_wire("U7,R6,D4,L4")];
+        assert_eq!(
+            intersection_hull(&wires),
+            vec![Point::new(3, 3), Point::new(6, 5)]
+        );
+    }
+
+    #[test]
+    fn test_steps_to_point() {
+        let wire = create_wire("R8,U5,L5,D3");
+        assert_eq!(steps_to_point(&wire, &Point::new(0, 0)), 0);
+        assert_eq!(steps_to_point(&wire, &Point::new(6, 5)), 15);
+        assert_eq!(steps_to_point(&wire, &Point::new(3, 3)), 20);
+    }
+
+    #[test]
+    fn test_find_intersections_dedups_corners() {
+        // Both wires turn a corner at (5, 0)
+        let wires = vec![create_wire("R5,U5"), create_wire("U3,R5,D3,R1")];
+        let mut intersections = find_intersections(&wires);
+        intersections.sort_by_key(|p| (p.x, p.y));
+        assert_eq!(intersections, vec![Point::new(5, 0), Point::new(5, 3)]);
+    }
+
+    #[test]
+    fn test_intersections_toroidal() {
+        // The second wire only reaches the first by wrapping around x=10
+        let wires = vec![create_wire("R3"), create_wire("U2,R12,D4")];
+        assert_eq!(find_intersections(&wires), vec![]);
+        assert_eq!(
+            intersections_toroidal(&wires, 10, 10),
+            vec![Point::new(2, 0)]
+        );
+
+        // A big enough torus is the same as the plane
+        let wires = vec![create_wire("R8,U5,L5,D3"), create_wire("U7,R6,D4,L4")];
+        assert_eq!(
+            intersections_toroidal(&wires, 100, 100),
+            vec![Point::new(3, 3), Point::new(6, 5)]
+        );
+
+        // Sizes that aren't positive don't make a torus
+        assert_eq!(intersections_toroidal(&wires, 0, 10), vec![]);
+        assert_eq!(intersections_toroidal(&wires, 10, 0), vec![]);
+        assert_eq!(intersections_toroidal(&wires, -10, 10), vec![]);
+    }
+
+    #[test]
+    fn test_shared_endpoint_crossing() {
+        // Both wires end at (5, 5)
+        let wires = vec![create_wire("R5,U5"), create_wire("U5,R5")];
+        assert_eq!(find_intersections(&wires), vec![Point::new(5, 5)]);
+        assert_eq!(
+            fewest_steps_intersection(&wires),
+            Some((Point::new(5, 5), 20))
+        );
+
+        // The second wire comes back along the first wire's row and stops
+        // where the first wire ends
+        let wires = vec![create_wire("U5,R5"), create_wire("R10,U5,L5")];
+        assert_eq!(find_intersections(&wires), vec![Point::new(5, 5)]);
+        assert_eq!(
+            fewest_steps_intersection(&wires),
+            Some((Point::new(5, 5), 30))
+        );
+    }
+
+    #[test]
+    fn test_intersection_count() {
+        assert_eq!(intersection_count(&example_wires()), 4);
+
+        let wires = vec![create_wire("R5,U5"), create_wire("U3,R5,D3,R1")];
+        assert_eq!(intersection_count(&wires), 2);
+
+        let wires = vec![create_wire("R8"), create_wire("U8")];
+        assert_eq!(intersection_count(&wires), 0);
+    }
+
+    #[test]
+    fn test_pairwise_intersection_counts() {
+        let wires = vec![
+            create_wire("R8,U5,L5,D3"),
+            create_wire("U7,R6,D4,L4"),
+            create_wire("D1,R7,U9"),
+        ];
+        assert_eq!(
+            pairwise_intersection_counts(&wires),
+            vec![vec![0, 2, 2], vec![2, 0, 0], vec![2, 0, 0]]
+        );
+        assert_eq!(pairwise_intersection_counts(&[]), Vec::<Vec<usize>>::new());
+    }
+
+    #[test]
+    fn test_intersections_centroid() {
+        // The wires mirror each other across the y axis, so the crossings
+        // at (0, 2), (-3, 1), and (3, 1) do too
+        let wires = vec![create_wire("L3,U2,R6,D2"), create_wire("U4,L5,D3,R10")];
+        let (x, y) = intersections_centroid(&wires).unwrap();
+        assert_eq!(x, 0.0);
+        assert!((y - 4.0 / 3.0).abs() < 1e-9);
+
+        let wires = vec![create_wire("R8"), create_wire("U8")];
+        assert_eq!(intersections_centroid(&wires), None);
+    }
+
+    #[test]
+    fn test_intersections_skip_first() {
+        // (3, 0) is on the first segment of the first wire
+        let wires = vec![create_wire("R5,U5"), create_wire("U2,R6,D4,L3,U4")];
+        assert_eq!(
+            intersections_skip_first(&wires, 0),
+            vec![Point::new(3, 0), Point::new(5, 2)]
+        );
+        assert_eq!(intersections_skip_first(&wires, 1), vec![Point::new(5, 2)]);
+        assert_eq!(intersections_skip_first(&wires, 2), vec![]);
+        assert_eq!(intersections_skip_first(&wires, 9), vec![]);
+
+        let wires = example_wires();
+        assert_eq!(
+            intersections_skip_first(&wires, 0),
+            find_intersections(&wires)
+        );
+    }
+
+    #[test]
+    fn test_intersections_snapped() {
+        let wires = vec![create_wire("R5,U5"), create_wire("U2,R8,U1,L8")];
+        assert_eq!(
+            intersections_snapped(&wires, 0),
+            vec![Point::new(5, 2), Point::new(5, 3)]
+        );
+        assert_eq!(intersections_snapped(&wires, 1), vec![Point::new(5, 2)]);
+
+        let wires = example_wires();
+        assert_eq!(intersections_snapped(&wires, 0), find_intersections(&wires));
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let wires = vec![create_wire("R8,U5,L5,D3"), create_wire("U7,R6,D4,L4")];
+        let svg = render_svg(&wires, &find_intersections(&wires));
+        assert!(
+            svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"-1 -8 10 9\">")
+        );
+        assert_eq!(svg.matches("<polyline").count(), 2);
+        assert_eq!(svg.matches("<circle").count(), 2);
+        assert!(svg.contains("points=\"0,0 8,0 8,-5 3,-5 3,-2\""));
+        assert!(svg.contains("<circle cx=\"3\" cy=\"-3\""));
+        assert_eq!(svg.matches("<rect").count(), 1);
+        assert!(svg.ends_with("</svg>\n"));
+
+        // Wires that don't start at the origin are drawn from their own start
+        let wires = vec![
+            translate_wire(&create_wire("R8,U5"), 2, 3),
+            reverse_wire(&create_wire("U4")),
+            vec![],
+        ];
+        let svg = render_svg(&wires, &[]);
+        assert!(svg.contains("points=\"2,-3 10,-3 10,-8\""));
+        assert!(svg.contains("points=\"0,-4 0,0\""));
+        assert_eq!(svg.matches("<polyline").count(), 2);
+    }
+
+    #[test]
+    fn test_intersections_within() {
+        let wires = vec![create_wire("R8,U5,L5,D3"), create_wire("U7,R6,D4,L4")];
+        assert_eq!(intersections_within(&wires, 5), vec![]);
+        assert_eq!(intersections_within(&wires, 6), vec![Point::new(3, 3)]);
+        assert_eq!(intersections_within(&wires, 10), vec![Point::new(3, 3)]);
+        assert_eq!(intersections_within(&wires, 11).len(), 2);
+    }
+
+    #[test]
+    fn test_wires_bounds() {
+        let wires = vec![create_wire("R8,U5,L5,D3"), create_wire("U7,R6,D4,L4")];
+        assert_eq!(
+            wires_bounds(&wires),
+            Some((Point::new(0, 0), Point::new(8, 7)))
+        );
+
+        let wires = vec![create_wire("L3,D2,R10"), create_wire("U4,L1")];
+        assert_eq!(
+            wires_bounds(&wires),
+            Some((Point::new(-3, -2), Point::new(7, 4)))
+        );
+
+        // Moved away from the origin, the box doesn't reach back to it
+        let wires = vec![translate_wire(&create_wire("R8,U5"), 10, 20)];
+        assert_eq!(
+            wires_bounds(&wires),
+            Some((Point::new(10, 20), Point::new(18, 25)))
+        );
+
+        assert_eq!(wires_bounds(&[]), None);
+        assert_eq!(wires_bounds(&[vec![]]), None);
+    }
+
+    #[test]
+    fn test_walk_length() {
+        assert_eq!(walk_length(&create_wire("R8,U5,L5,D3")), 21);
+        assert_eq!(walk_length(&example_wires()[1]), 484);
+        assert_eq!(walk_length(&[]), 0);
+    }
+
+    #[test]
+    fn test_steps_along() {
+        let wire = create_wire("R8,U5,L5,D3");
+        // Mid-segment
+        assert_eq!(steps_along(&wire, &Point::new(4, 0)), Some(4));
+        assert_eq!(steps_along(&wire, &Point::new(5, 5)), Some(16));
+        // Corners
+        assert_eq!(steps_along(&wire, &Point::new(8, 0)), Some(8));
+        assert_eq!(steps_along(&wire, &Point::new(3, 5)), Some(18));
+        assert_eq!(steps_along(&wire, &Point::new(3, 2)), Some(21));
+        // Not on the wire
+        assert_eq!(steps_along(&wire, &Point::new(4, 1)), None);
+        assert_eq!(steps_along(&wire, &Point::new(9, 0)), None);
+    }
+
+    #[test]
+    fn test_fewest_steps_intersection() {
+        let (_, steps) = fewest_steps_intersection(&example_wires()).unwrap();
+        assert_eq!(steps, 610);
+
+        let wires = vec![create_wire("R8,U5,L5,D3"), create_wire("U7,R6,D4,L4")];
+        assert_eq!(
+            fewest_steps_intersection(&wires),
+            Some((Point::new(6, 5), 30))
+        );
+    }
+
+    #[test]
+    fn test_most_steps_intersection() {
+        let wires = example_wires();
+        assert_eq!(
+            most_steps_intersection(&wires),
+            Some((Point::new(155, 11), 850))
+        );
+
+        let wires = vec![create_wire("R8,U5,L5,D3"), create_wire("U7,R6,D4,L4")];
+        assert_eq!(
+            most_steps_intersection(&wires),
+            Some((Point::new(3, 3), 40))
+        );
+
+        let wires = vec![create_wire("R8"), create_wire("U8")];
+        assert_eq!(most_steps_intersection(&wires), None);
+    }
+
+    #[test]
+    fn test_solution() {
+        use common::Solution;
+
+        let input = "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51\n\
+                     U98,R91,D20,R16,D67,R40,U7,R15,U6,R7\n";
+        assert_eq!(Day3.part1(input), "135");
+        assert_eq!(Day3.part2(input), "410");
+        assert_eq!(Day3.part1("R8\nU8\n"), "none");
+    }
+
+    #[test]
+    fn test_closest_intersection_none() {
+        let wires = vec![create_wire("R8"), create_wire("U8")];
+        assert_eq!(closest_intersection(&wires), None);
+        assert_eq!(fewest_steps_intersection(&wires), None);
+    }
+}
+
+/// Formats the items with Display, separated by ", "
+fn join_display<T: fmt::Display>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod test_join_display {
+    use super::*;
+
+    #[test]
+    fn test_join_display() {
+        let points = [Point::new(1, 2), Point::new(-3, 4)];
+        assert_eq!(
+            join_display(&points),
+            format!("{}, {}", points[0], points[1])
+        );
+        assert_eq!(join_display::<Point>(&[]), "");
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let json = common::wants_json(&args);
+    let part = match common::selected_part(&args) {
+        Ok(part) => part,
+        Err(e) => {
+            eprintln!("Error: {}.", e);
+            std::process::exit(common::exit_code_for(&e));
+        }
+    };
+
+    // First arg is the data file path; read stdin if there isn't one
+    let contents = match common::read_input(common::first_positional(&args)) {
+        Err(e) => {
+            eprintln!("Can't read input: {}", e);
+            std::process::exit(common::exit_code_for(&e.into()));
+        }
+        Ok(contents) => contents,
+    };
+
+    // Parse the wires
+    let wires = parse_wires(&contents);
+    let closest = part.part1().then(|| closest_intersection(&wires)).flatten();
+    let fewest_steps = part
+        .part2()
+        .then(|| fewest_steps_intersection(&wires))
+        .flatten();
+
+    if json {
+        if (part.part1() && closest.is_none()) || (part.part2() && fewest_steps.is_none()) {
+            let e = common::AocError::Data("the wires don't intersect".to_string());
+            eprintln!("Error: {}.", e);
+            std::process::exit(common::exit_code_for(&e));
+        }
+        println!(
+            "{}",
+            common::format_json_parts(
+                closest.map(|(_, distance)| distance.into()),
+                fewest_steps.map(|(_, steps)| steps.into())
+            )
+        );
+        return;
+    }
+
+    for wire in wires.iter() {
+        println!("parsed: {}", join_display(wire));
+    }
+
+    let intersections = find_intersections(&wires);
+    println!("intersections: {}", join_display(&intersections));
+
+    if part.part1() {
+        match closest {
+            Some((point, distance)) => {
+                println!("Minimum distance from origin: {} at {}", distance, point);
+            }
+            None => {
+                println!("No minimum distance from origin was found.");
+            }
+        }
+    }
+
+    if part.part2() {
+        match fewest_steps {
+            Some((point, steps)) => {
+                println!("Minimum steps: {} at {}", steps, point);
+            }
+            None => {
+                println!("No minimum steps was found.");
+            }
         }
     }
 }